@@ -0,0 +1,240 @@
+//! the tabs, and the loop that feeds them
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::widgets::Tabs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cargo::{self, TestMessage};
+use crate::compiler;
+use crate::graph::Graph;
+use crate::lints::Lints;
+use crate::runner;
+use crate::test::{self, history::History, stress::Stress, TestState};
+use crate::ui::{editor, log::Log, *};
+use crate::Cmd;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Build,
+    Tests,
+    Log,
+}
+
+impl Tab {
+    fn all(cmd: &Cmd) -> &'static [Tab] {
+        match cmd {
+            Cmd::Test | Cmd::Stress { .. } => &[Tab::Build, Tab::Tests, Tab::Log],
+            // no tests to run
+            Cmd::Clippy => &[Tab::Build, Tab::Log],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Tab::Build => "build",
+            Tab::Tests => "tests",
+            Tab::Log => "log",
+        }
+    }
+}
+
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    dir: Option<&Path>,
+    meta: &cargo::Metadata,
+    graph: &Graph,
+    opts: &cargo::Options,
+    cmd: &Cmd,
+    test_opts: test::Options,
+) -> Result<()> {
+    let (mut rx, pid) = match cmd {
+        // built first, then run by us
+        Cmd::Test if opts.isolate.is_some() => cargo::build_tests(dir, opts)?,
+        Cmd::Test => cargo::test(dir, opts)?,
+        Cmd::Clippy => cargo::clippy(dir, opts)?,
+        Cmd::Stress { .. } => cargo::build_tests(dir, opts)?,
+    };
+    let mut build = compiler::State::new(pid, opts, Lints::new(dir, *cmd == Cmd::Clippy));
+    // the defaults are for stressing from the dashboard
+    let (mut pending, runs, jobs) = match cmd {
+        Cmd::Stress { test, runs, jobs } => (Some(test.clone()), *runs, *jobs),
+        _ => (None, 100, 1),
+    };
+    let mut isolate = opts.isolate.filter(|_| *cmd == Cmd::Test);
+    let timeouts = test_opts.timeouts.clone();
+    // runs some tests again, the same way as the first time
+    let again = |binaries: &[runner::TestBinary], names| match opts.isolate {
+        Some(jobs) => runner::isolated(dir, binaries, Some(names), jobs, opts, &timeouts),
+        None => runner::rerun(dir, binaries, names, opts),
+    };
+    let mut tests = TestState::new(
+        test_opts,
+        graph.meta.workspace_root.clone().into_std_path_buf(),
+        History::load(
+            graph.meta.target_directory.as_std_path(),
+            graph.meta.workspace_root.as_std_path(),
+        ),
+    );
+    // whose descendants are the running test binaries
+    let mut current = pid;
+    // the tests that were in binaries that got killed, and still need to run
+    let mut leftover = vec![];
+    let cwd = runner::cwd(dir);
+    let mut log = Log::default();
+    let mut tab = Tab::Build;
+    print!("\x1b]0;compiling {}\x07", meta.package.name);
+    loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Vertical)
+                .constraints([Length(1), Min(1)])
+                .split(f.size());
+            tabs(f, cmd, tab, &build, &tests, chunks[0]);
+            match tab {
+                Tab::Build => compiler::ui::ui(f, &mut build, meta, graph, chunks[1]),
+                Tab::Tests => crate::test::ui::ui(f, &mut tests, meta, chunks[1]),
+                Tab::Log => crate::ui::log::log(f, &mut log, chunks[1]),
+            }
+        })?;
+        if event::poll(Duration::from_millis(5))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    // typing a search
+                    code if tab == Tab::Tests && tests.typing() => tests.key(code)?,
+                    KeyCode::Char('q') => return Ok(()),
+                    KeyCode::Char(n @ '1'..='9')
+                        if Tab::all(cmd).len() > n as usize - '1' as usize =>
+                    {
+                        tab = Tab::all(cmd)[n as usize - '1' as usize]
+                    }
+                    code => match tab {
+                        Tab::Build => build.key(code, graph),
+                        Tab::Tests => tests.key(code)?,
+                        Tab::Log => log.key(code),
+                    },
+                }
+            }
+        }
+        if let Some((file, line, col)) = tests.edit.take() {
+            editor::open(terminal, &file, line, col)?;
+        }
+        if let Some(name) = tests.stress_request.take() {
+            let timeout = timeouts.of(&name);
+            let rx = runner::stress(dir, build.tests(), name.clone(), runs, jobs, opts, timeout);
+            tests.stress(Stress::new(name, runs, rx));
+            tab = Tab::Tests;
+        }
+        if let Some(names) = tests.rerun.take() {
+            rx = again(build.tests(), names);
+            tests.done = false;
+        }
+        build.tick();
+        tests.tick();
+        tests.sample(build.tests(), &cwd);
+        if tests.done {
+            continue;
+        }
+        // isolated tests are timed by the runner
+        while let Some(name) = tests.hung().pop().filter(|_| opts.isolate.is_none()) {
+            let (binary, listed) = match runner::kill(current, build.tests(), &name) {
+                Some((b, listed)) => (Some(b), listed),
+                None => {
+                    ::log::error!("couldnt find the process running {name}");
+                    (None, vec![])
+                }
+            };
+            let left = tests.timed_out(&name, &listed);
+            if let Some(b) = binary.filter(|_| !left.is_empty()) {
+                leftover.push((b, left));
+            }
+        }
+        let deadline = Instant::now() + Duration::from_millis(50);
+        while let Ok(event) = rx.recv_deadline(deadline) {
+            match event {
+                TestMessage::CompilerEvent(e) => {
+                    build.handle(*e, graph);
+                    if build.done && !build.failed() && *cmd != Cmd::Clippy {
+                        print!("\x1b]0;testing {}\x07", meta.package.name);
+                        if tab == Tab::Build {
+                            tab = Tab::Tests;
+                        }
+                    }
+                }
+                TestMessage::Event(e) => tests.handle(e),
+                // named like the runner names them, so reruns find their tests
+                TestMessage::Suite { name, binary } => tests.suite(
+                    binary
+                        .and_then(|p| build.tests().iter().find(|b| b.path.ends_with(&p)))
+                        .map_or(name, |b| b.suite(&cwd)),
+                ),
+                TestMessage::Running(s) => tests.running(s),
+                TestMessage::Spawned(p) => current = p,
+                TestMessage::Pool(n) => tests.pool(n),
+                TestMessage::Isolated { suite, event } => tests.isolated(suite, event),
+                TestMessage::Exited { suite, name, exit } => tests.exited(suite, name, *exit),
+                TestMessage::Finished => {
+                    tests.crashed();
+                    if let Some((b, names)) = leftover.pop() {
+                        ::log::info!("running the {} tests left after a timeout", names.len());
+                        tests.resume();
+                        rx = runner::rerun(dir, &[b], names, opts);
+                        break;
+                    }
+                    // the tests are built, now to run them
+                    if let Some(jobs) = isolate.take().filter(|_| !build.failed()) {
+                        rx = runner::isolated(dir, build.tests(), None, jobs, opts, &timeouts);
+                        break;
+                    }
+                    let retry = tests.retry();
+                    if !retry.is_empty() {
+                        rx = again(build.tests(), retry);
+                        break;
+                    }
+                    tests.finished();
+                    // the tests to stress are built
+                    if let Some(test) = pending.take().filter(|_| !build.failed()) {
+                        tests.stress_request = Some(test);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn tabs<B: Backend>(
+    f: &mut Frame<B>,
+    cmd: &Cmd,
+    tab: Tab,
+    build: &compiler::State,
+    tests: &TestState,
+    chunk: Rect,
+) {
+    let titles = Tab::all(cmd)
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let status = match t {
+                Tab::Build if build.failed() => "✗".red(),
+                Tab::Build if build.done => "✓".green(),
+                Tab::Build => "…".yellow(),
+                Tab::Tests if tests.done => "✓".green(),
+                Tab::Tests if build.done => "…".yellow(),
+                _ => "".into(),
+            };
+            Line::from(vec![
+                Span::styled(format!("{} ", i + 1), Style::default().dark_gray()),
+                t.name().into(),
+                " ".into(),
+                status,
+            ])
+        })
+        .collect();
+    f.render_widget(
+        Tabs::new(titles)
+            .select(Tab::all(cmd).iter().position(|&t| t == tab).unwrap())
+            .highlight_style(Style::default().bold().on_light_green()),
+        chunk,
+    );
+}
@@ -0,0 +1,196 @@
+//! binary size breakdown, like `cargo bloat`
+use anyhow::Result;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use crossbeam::channel::{unbounded, Receiver};
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::ui::SList;
+
+pub mod ui;
+
+/// how many functions to keep
+const TOP: usize = 100;
+
+pub struct Artifact {
+    pub name: String,
+    /// the package id and target, stable across runs, used for the history
+    pub key: String,
+    pub path: Utf8PathBuf,
+}
+
+pub struct Report {
+    pub size: u64,
+    pub text: u64,
+    pub sections: Vec<(String, u64)>,
+    pub crates: Vec<(String, u64)>,
+    pub functions: Vec<(String, u64)>,
+    /// sizes from previous runs, oldest first
+    pub history: Vec<u64>,
+}
+
+impl Report {
+    /// growth since the last run
+    pub fn delta(&self) -> Option<i64> {
+        Some(self.size as i64 - *self.history.last()? as i64)
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    Sections,
+    Crates,
+    Functions,
+}
+
+#[derive(Default)]
+pub struct State {
+    pub artifacts: Vec<Artifact>,
+    pub reports: Vec<Option<Result<Report, String>>>,
+    pub list: SList,
+    pub view: View,
+    pub scroll: u16,
+    rx: Option<Receiver<(usize, Result<Report, String>)>>,
+}
+
+impl State {
+    /// analyzes the artifacts in the background
+    pub fn new(artifacts: Vec<Artifact>, target: &Utf8Path) -> Self {
+        let (tx, rx) = unbounded();
+        let jobs = artifacts
+            .iter()
+            .map(|a| (a.key.clone(), a.path.clone()))
+            .collect::<Vec<_>>();
+        let store = target.join("kewl").join("sizes.json");
+        std::thread::spawn(move || {
+            let mut history = load(&store);
+            for (i, (name, path)) in jobs.into_iter().enumerate() {
+                let report = analyze(&path).map(|mut r| {
+                    let h = history.entry(name).or_default();
+                    r.history = h.clone();
+                    h.push(r.size);
+                    r
+                });
+                if tx.send((i, report.map_err(|e| e.to_string()))).is_err() {
+                    return;
+                }
+            }
+            if let Err(e) = save(&store, &history) {
+                log::error!("couldnt save size history: {e}");
+            }
+        });
+        Self {
+            reports: artifacts.iter().map(|_| None).collect(),
+            artifacts,
+            rx: Some(rx),
+            ..Self::default()
+        }
+    }
+
+    pub fn recv(&mut self) {
+        let Some(rx) = &self.rx else { return };
+        while let Ok((i, report)) = rx.try_recv() {
+            self.reports[i] = Some(report);
+        }
+    }
+
+    pub fn selects(&self) -> Option<(&Artifact, Option<&Result<Report, String>>)> {
+        let i = self.list.state.selected()?;
+        Some((self.artifacts.get(i)?, self.reports.get(i)?.as_ref()))
+    }
+
+    /// down a line, as far as the rows of the view go
+    pub fn scroll_down(&mut self) {
+        let rows = match self.selects() {
+            Some((_, Some(Ok(r)))) => match self.view {
+                View::Sections => r.sections.len(),
+                View::Crates => r.crates.len(),
+                View::Functions => r.functions.len(),
+            },
+            _ => 0,
+        };
+        self.scroll = (self.scroll + 1).min(rows.saturating_sub(1) as u16);
+    }
+
+    pub fn next_view(&mut self) {
+        self.scroll = 0;
+        self.view = match self.view {
+            View::Sections => View::Crates,
+            View::Crates => View::Functions,
+            View::Functions => View::Sections,
+        };
+    }
+}
+
+pub fn analyze(path: &Utf8Path) -> Result<Report> {
+    log::debug!("analyzing {path}");
+    let data = std::fs::read(path)?;
+    let file = object::File::parse(&*data)?;
+    let mut sections = file
+        .sections()
+        .filter(|s| s.size() != 0)
+        .map(|s| (s.name().unwrap_or("?").to_string(), s.size()))
+        .collect::<Vec<_>>();
+    sections.sort_unstable_by_key(|&(_, n)| Reverse(n));
+    let text = sections
+        .iter()
+        .find(|(name, _)| name == ".text")
+        .map_or(0, |&(_, n)| n);
+    let mut crates = HashMap::<String, u64>::new();
+    let mut functions = vec![];
+    for sym in file.symbols() {
+        if sym.kind() != SymbolKind::Text || sym.size() == 0 {
+            continue;
+        }
+        let Ok(name) = sym.name() else { continue };
+        let name = format!("{:#}", rustc_demangle::demangle(name));
+        *crates.entry(krate(&name).to_string()).or_default() += sym.size();
+        functions.push((name, sym.size()));
+    }
+    let mut crates = crates.into_iter().collect::<Vec<_>>();
+    crates.sort_unstable_by_key(|&(_, n)| Reverse(n));
+    functions.sort_unstable_by_key(|&(_, n)| Reverse(n));
+    functions.truncate(TOP);
+    Ok(Report {
+        size: data.len() as u64,
+        text,
+        sections,
+        crates,
+        functions,
+        history: vec![],
+    })
+}
+
+/// guess the crate a function came from
+fn krate(sym: &str) -> &str {
+    let path = sym.trim_start_matches(['<', '&', '*']);
+    let path = path.strip_prefix("mut ").unwrap_or(path);
+    let path = path.strip_prefix("dyn ").unwrap_or(path);
+    match path.split_once("::") {
+        Some((krate, _)) if !krate.contains([' ', '<', '>']) => krate,
+        _ => "[unknown]",
+    }
+}
+
+fn load(at: &Utf8Path) -> HashMap<String, Vec<u64>> {
+    std::fs::read(at)
+        .ok()
+        .and_then(|f| serde_json::from_slice(&f).ok())
+        .unwrap_or_default()
+}
+
+fn save(at: &Utf8Path, history: &HashMap<String, Vec<u64>>) -> Result<()> {
+    std::fs::create_dir_all(at.parent().unwrap())?;
+    std::fs::write(at, serde_json::to_vec(history)?)?;
+    Ok(())
+}
+
+/// 1.2 KiB, 300 B, etc
+pub fn bytes(n: u64) -> String {
+    match n {
+        0..=1023 => format!("{n} B"),
+        1024..=1048575 => format!("{:.1} KiB", n as f64 / 1024.),
+        _ => format!("{:.1} MiB", n as f64 / 1048576.),
+    }
+}
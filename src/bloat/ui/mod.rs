@@ -0,0 +1,135 @@
+use super::{bytes, Report, State, View};
+use crate::ui::*;
+use ratatui::widgets::Sparkline;
+
+pub fn bloat<B: Backend>(f: &mut Frame<B>, state: &mut State, chunk: Rect) {
+    let chunks = Layout::default()
+        .direction(Horizontal)
+        .constraints([Percentage(30), Percentage(70)])
+        .split(chunk);
+    let mut l = Vec::with_capacity(state.artifacts.len());
+    for (a, r) in state.artifacts.iter().zip(&state.reports) {
+        match r {
+            None => l.pl(vec![a.name.as_str().blue(), " analyzing".yellow().italic()]),
+            Some(Err(_)) => l.pl(vec![a.name.as_str().blue(), " failed".red().italic()]),
+            Some(Ok(r)) => {
+                let mut line = vec![
+                    a.name.as_str().blue(),
+                    Span::raw(format!(" {}", bytes(r.size))),
+                ];
+                line.extend(delta(r));
+                l.pl(line);
+            }
+        }
+    }
+    state.list.has(l.len());
+    f.render_stateful_widget(
+        List::new(l)
+            .highlight_style(Style::default().on_light_green().italic())
+            .highlight_symbol("> ")
+            .block(Block::default().title("executables").borders(Borders::ALL)),
+        chunks[0],
+        &mut state.list.state,
+    );
+    let b = Block::default().title("size").borders(Borders::ALL);
+    let Some((a, r)) = state.selects() else {
+        f.render_widget(
+            Paragraph::new("select an executable to see what its made of")
+                .alignment(Alignment::Center)
+                .block(b),
+            chunks[1],
+        );
+        return;
+    };
+    let r = match r {
+        None => {
+            return f.render_widget(
+                Paragraph::new(ctext!("analyzing {:bold_yellow}", a.path))
+                    .alignment(Alignment::Center)
+                    .block(b),
+                chunks[1],
+            )
+        }
+        Some(Err(e)) => {
+            return f.render_widget(
+                Paragraph::new(ctext!("couldnt read {:bold_red}: {}", a.path, e))
+                    .block(b)
+                    .wrap(Wrap { trim: true }),
+                chunks[1],
+            )
+        }
+        Some(Ok(r)) => r,
+    };
+    let side = Layout::default()
+        .direction(Vertical)
+        .constraints([Length(3), Length(4), Min(1)])
+        .split(chunks[1]);
+    let mut summary = vec![
+        "file ".into(),
+        Span::styled(bytes(r.size), Style::default().bold()),
+        "  .text ".into(),
+        Span::styled(bytes(r.text), Style::default().bold()),
+    ];
+    summary.extend(delta(r));
+    f.render_widget(
+        Paragraph::new(Line::from(summary)).block(b.title(a.path.as_str())),
+        side[0],
+    );
+    let mut history = r.history.clone();
+    history.push(r.size);
+    let min = history.iter().min().copied().unwrap_or(0);
+    // make the changes visible, not the absolute size
+    let history = history.iter().map(|&n| n - min + 1).collect::<Vec<_>>();
+    f.render_widget(
+        Sparkline::default()
+            .data(&history)
+            .style(Style::default().cyan())
+            .block(Block::default().title("history").borders(Borders::ALL)),
+        side[1],
+    );
+    let (title, rows) = match state.view {
+        View::Sections => ("sections", &r.sections),
+        View::Crates => ("crates", &r.crates),
+        View::Functions => ("largest functions", &r.functions),
+    };
+    let total = match state.view {
+        View::Sections => r.size,
+        _ => r.text.max(1),
+    };
+    let mut t = Vec::with_capacity(rows.len());
+    for (name, size) in rows {
+        let pct = *size as f64 / total as f64 * 100.;
+        t.push(Line::from(vec![
+            Span::styled(format!("{:>10} ", bytes(*size)), Style::default().bold()),
+            Span::styled(format!("{pct:>5.1}% "), Style::default().dark_gray()),
+            Span::styled(
+                "█".repeat((pct / 5.).ceil() as usize),
+                Style::default().magenta(),
+            ),
+            Span::raw(format!(" {name}")),
+        ]));
+    }
+    if t.is_empty() {
+        t.push(Line::from("no symbols; is the binary stripped?".italic()));
+    }
+    f.render_widget(
+        Paragraph::new(t)
+            .scroll((state.scroll, 0))
+            .block(Block::default().title(title).borders(Borders::ALL)),
+        side[2],
+    );
+}
+
+fn delta<'a>(r: &Report) -> Option<Span<'a>> {
+    match r.delta()? {
+        0 => Some(" ±0".dark_gray()),
+        d if d > 0 => Some(Span::styled(
+            format!(" +{}", bytes(d as u64)),
+            Style::default().red(),
+        )),
+        d => Some(Span::styled(
+            format!(" -{}", bytes(d.unsigned_abs())),
+            Style::default().green(),
+        )),
+    }
+}
@@ -1,34 +1,112 @@
 use anyhow::Result;
+use cargo_metadata::camino::Utf8PathBuf;
 pub use cargo_metadata::{
     libtest::SuiteEvent, libtest::TestEvent, Message, TestMessage as RawTestMessage,
 };
-use crossbeam::channel::bounded;
+use crossbeam::channel::unbounded;
 use crossbeam::channel::Receiver;
 use serde_derive::Deserialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{
-    io::Read,
+    io::{BufRead, BufReader, Read},
     process::{Command, Stdio},
 };
 
 #[derive(Debug)]
 pub enum TestMessage {
-    CompilerEvent(Message),
+    CompilerEvent(Box<Message>),
     Event(RawTestMessage),
+    /// cargo is about to run a test binary, like `src/main.rs`, at `binary` if it said
+    Suite {
+        name: String,
+        binary: Option<Utf8PathBuf>,
+    },
+    /// we are about to run a test binary, named like [`crate::runner::TestBinary::suite`]
+    Running(String),
+    /// we started a test binary ourselves, with this pid
+    Spawned(u32),
+    /// running this many tests, each in its own process
+    Pool(usize),
+    /// from a test running in its own process, in the binary `suite`
+    Isolated {
+        suite: String,
+        event: RawTestMessage,
+    },
+    /// that process ended
+    Exited {
+        suite: String,
+        name: String,
+        exit: Box<crate::runner::Exit>,
+    },
     Finished,
 }
 
-pub fn test(at: Option<&Path>) -> Result<Receiver<TestMessage>> {
-    let (tx, rx) = bounded(10);
+#[derive(Default)]
+pub struct Options {
+    /// where to put `-Ztime-passes` output, if wanted
+    pub passes: Option<PathBuf>,
+    /// `RUST_BACKTRACE` for the tests
+    pub backtrace: Option<Backtrace>,
+    /// run each test in its own process, this many at once, instead of through `cargo test`
+    pub isolate: Option<usize>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum Backtrace {
+    Short,
+    Full,
+}
+
+impl Backtrace {
+    /// sets `RUST_BACKTRACE`, which the test binaries inherit
+    pub fn set(self, proc: &mut Command) {
+        proc.env(
+            "RUST_BACKTRACE",
+            match self {
+                Backtrace::Short => "1",
+                Backtrace::Full => "full",
+            },
+        );
+    }
+}
+
+pub fn cargo(at: Option<&Path>) -> Command {
     let mut proc = Command::new("cargo");
     if let Some(at) = at {
         proc.arg("-C");
         proc.arg(at.as_os_str());
     }
+    proc
+}
+
+/// makes us the wrapper, if the passes are wanted
+fn time_passes(proc: &mut Command, opts: &Options) -> Result<()> {
+    if let Some(passes) = &opts.passes {
+        // whats there is from older builds, and units that dont compile now would show it
+        if let Err(e) = std::fs::remove_dir_all(passes) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("couldnt clear {}: {e}", passes.display());
+            }
+        }
+        proc.env("RUSTC_WORKSPACE_WRAPPER", std::env::current_exe()?);
+        proc.env(crate::passes::ENV, passes);
+    }
+    Ok(())
+}
+
+/// returns the events and the pid of cargo
+pub fn test(at: Option<&Path>, opts: &Options) -> Result<(Receiver<TestMessage>, u32)> {
+    let mut proc = cargo(at);
     // proc.env("RUSTFLAGS", format!("--diagnostic-width={width}"));
+    time_passes(&mut proc, opts)?;
+    if let Some(b) = opts.backtrace {
+        b.set(&mut proc);
+    }
     proc.args([
         "-Zunstable-options",
         "test",
+        // a killed binary shouldnt stop the rest
+        "--no-fail-fast",
         "--message-format",
         "json",
         "--",
@@ -38,8 +116,49 @@ pub fn test(at: Option<&Path>) -> Result<Receiver<TestMessage>> {
         "--format",
         "json",
     ]);
+    run(proc)
+}
+
+/// builds the tests, without running them
+pub fn build_tests(at: Option<&Path>, opts: &Options) -> Result<(Receiver<TestMessage>, u32)> {
+    let mut proc = cargo(at);
+    time_passes(&mut proc, opts)?;
+    proc.args(["test", "--no-run", "--message-format", "json"]);
+    run(proc)
+}
+
+/// like [`test`], but only compiler events
+pub fn clippy(at: Option<&Path>, opts: &Options) -> Result<(Receiver<TestMessage>, u32)> {
+    if opts.passes.is_some() {
+        // clippy is the wrapper
+        log::warn!("cant time passes under clippy");
+    }
+    let mut proc = cargo(at);
+    proc.args(["clippy", "--all-targets", "--message-format", "json"]);
+    run(proc)
+}
+
+fn run(mut proc: Command) -> Result<(Receiver<TestMessage>, u32)> {
+    // unbounded, a full channel would stall the stderr reader and so cargo
+    let (tx, rx) = unbounded();
     log::trace!("running {proc:?}");
-    let mut proc = proc.stdout(Stdio::piped()).stderr(Stdio::null()).spawn()?;
+    let mut proc = proc
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = proc.id();
+    let err = proc.stderr.take().unwrap();
+    // for the log tab
+    let etx = tx.clone();
+    std::thread::spawn(move || {
+        for line in BufReader::new(err).lines().map_while(Result::ok) {
+            log::info!("cargo: {line}");
+            if let Some((name, binary)) = suite(&line) {
+                _ = etx.send(TestMessage::Suite { name, binary });
+            }
+        }
+    });
     let mut out = proc.stdout.take().unwrap();
     let mut tmp = Vec::with_capacity(32);
     let mut stdout = [0; 4096];
@@ -51,15 +170,20 @@ pub fn test(at: Option<&Path>) -> Result<Receiver<TestMessage>> {
                 match byte {
                     b'\n' => {
                         let val = serde_json::from_slice::<serde_json::Value>(&tmp).unwrap();
-                        log::debug!("got val: {}", serde_json::to_string_pretty(&val).unwrap());
+                        if log::log_enabled!(log::Level::Debug) {
+                            log::debug!("got val: {}", serde_json::to_string_pretty(&val).unwrap());
+                        }
                         let event = match serde_json::value::from_value::<Message>(val.clone()) {
                             Err(_) => TestMessage::Event(
                                 serde_json::value::from_value::<RawTestMessage>(val).unwrap(),
                             ),
-                            Ok(v) => TestMessage::CompilerEvent(v),
+                            Ok(v) => TestMessage::CompilerEvent(Box::new(v)),
                         };
                         tmp.clear();
-                        tx.send(event).unwrap();
+                        // nobody is listening anymore
+                        if tx.send(event).is_err() {
+                            return;
+                        }
                     }
                     b => tmp.push(b),
                 }
@@ -75,18 +199,57 @@ pub fn test(at: Option<&Path>) -> Result<Receiver<TestMessage>> {
                 }
                 handle!(n);
             }
-            tx.send(TestMessage::Finished).unwrap();
+            _ = tx.send(TestMessage::Finished);
             log::debug!("proc exited, joining thread");
             break;
         }
         std::thread::sleep(std::time::Duration::from_millis(50));
     });
-    Ok(rx)
+    Ok((rx, pid))
+}
+
+/// the name and path of a test binary from cargos `Running unittests src/main.rs (target/..)`
+fn suite(line: &str) -> Option<(String, Option<Utf8PathBuf>)> {
+    let line = line.trim_start();
+    if let Some(krate) = line.strip_prefix("Doc-tests ") {
+        return Some((format!("doctests {krate}"), None));
+    }
+    let running = line.strip_prefix("Running ")?;
+    // not `-v`s ``Running `rustc ..` ``
+    if running.starts_with('`') {
+        return None;
+    }
+    let (running, binary) = running.strip_suffix(')')?.rsplit_once(" (")?;
+    let running = running.strip_prefix("unittests ").unwrap_or(running);
+    Some((running.to_string(), Some(binary.into())))
+}
+
+/// the `-j` cargo will use
+pub fn jobs() -> usize {
+    std::env::var("CARGO_BUILD_JOBS")
+        .ok()
+        .and_then(|j| j.parse().ok())
+        .or_else(|| std::thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
 }
 
 #[derive(Deserialize)]
 pub struct Package {
     pub name: String,
+    #[serde(default)]
+    pub metadata: PackageMetadata,
+}
+
+#[derive(Deserialize, Default)]
+pub struct PackageMetadata {
+    #[serde(default)]
+    pub kewl: Config,
+}
+
+/// `[package.metadata.kewl]`, for what the flags would say every time
+#[derive(Deserialize, Default)]
+pub struct Config {
+    pub retries: Option<usize>,
 }
 
 #[derive(Deserialize)]
@@ -99,3 +262,40 @@ pub fn meta(at: &Path) -> Result<Metadata> {
         at.join("Cargo.toml"),
     )?)?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running() {
+        assert_eq!(
+            suite("     Running unittests src/main.rs (target/debug/deps/demo-0a8c1f2b3c4d5e6f)"),
+            Some((
+                "src/main.rs".to_string(),
+                Some("target/debug/deps/demo-0a8c1f2b3c4d5e6f".into())
+            ))
+        );
+        assert_eq!(
+            suite("     Running tests/it.rs (target/debug/deps/it-9f8e7d6c5b4a3f2e)"),
+            Some((
+                "tests/it.rs".to_string(),
+                Some("target/debug/deps/it-9f8e7d6c5b4a3f2e".into())
+            ))
+        );
+        assert_eq!(
+            suite("   Doc-tests demo"),
+            Some(("doctests demo".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn not_running() {
+        assert_eq!(
+            suite("     Running `rustc --crate-name demo --edition=2021 src/main.rs`"),
+            None
+        );
+        assert_eq!(suite("   Compiling demo v0.1.0 (/tmp/demo)"), None);
+        assert_eq!(suite("     Running something"), None);
+    }
+}
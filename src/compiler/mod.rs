@@ -1,24 +1,32 @@
 //! compiler output ui
-use anyhow::Result;
-use cargo_metadata::{diagnostic::Diagnostic, CompilerMessage, Message, PackageId};
-use crossbeam::channel::Receiver;
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::prelude::*;
+use cargo_metadata::{diagnostic::DiagnosticLevel, CompilerMessage, Message, PackageId};
+use crossterm::event::KeyCode;
 use std::{
-    ops::ControlFlow,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
 use crate::{
-    cargo::{self, TestMessage},
+    bloat::{self, Artifact},
+    cargo,
+    graph::Graph,
+    lints::{self, Lints},
+    passes, proc,
+    runner::TestBinary,
     ui::SList,
 };
 
-mod ui;
+mod timings;
+pub mod ui;
+mod warnings;
+pub use timings::Timings;
+pub use warnings::Diagnostics;
 
 const BUILT_SCRIPT: u8 = 1;
 const BUILD_SCRIPT_EXECUTED: u8 = 2;
 const FINISHED: u8 = 4;
+const FAILED: u8 = 8;
 
 struct Crate {
     pid: PackageId,
@@ -26,165 +34,509 @@ struct Crate {
     state: u8,
 }
 
-struct State {
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Waiting,
+    Building,
+    Built,
+    Failed,
+    /// a dependency failed
+    Blocked,
+}
+
+#[derive(Default, PartialEq, Eq)]
+enum View {
+    #[default]
+    List,
+    Tree,
+    Timings,
+    Bloat,
+    Warnings,
+}
+
+/// the unit a rustc is compiling, as its root source file and if its a test
+fn compiling(pid: u32) -> Option<(PathBuf, bool)> {
+    let args = proc::cmdline(pid);
+    let src = args
+        .iter()
+        .skip(1)
+        .find(|a| !a.starts_with('-') && a.ends_with(".rs"))?;
+    let test = args.iter().any(|a| a == "--test");
+    Some((proc::cwd(pid)?.join(src), test))
+}
+
+pub struct State {
     compiled: SList,
+    tree: SList,
+    view: View,
     crates: Vec<Crate>,
-    diagnostics: Vec<String>,
-    rx: Receiver<TestMessage>,
+    diagnostics: Diagnostics,
+    overview: warnings::Overview,
+    /// the code or lint the diagnostics pane is explaining instead, and how far down
+    explaining: Option<(String, u16)>,
+    lints: Lints,
+    executables: Vec<Artifact>,
+    /// kept for reruns
+    tests: Vec<TestBinary>,
+    bloat: bloat::State,
+    timings: Timings,
+    started: Instant,
+    /// of cargo
+    pid: u32,
+    cpu: proc::Cpu,
+    last_sample: Instant,
+    /// where the `-Ztime-passes` go
+    passes: Option<std::path::PathBuf>,
+    /// when each crate could start compiling: when its last dependency, or its last unit, finished.
+    /// cargo doesnt say when it starts a unit, this is the guess for the ones the samples missed.
+    ready: HashMap<PackageId, Instant>,
+    /// when the samples first saw a rustc compiling a unit, by [`compiling`]
+    began: HashMap<(PathBuf, bool), Instant>,
+    /// the samples have seen a rustc, so a unit they didnt see started after the last one
+    sees_rustc: bool,
     failed: bool,
+    /// got `BuildFinished`
+    pub done: bool,
 }
 
 impl State {
-    fn new(rx: Receiver<TestMessage>) -> Self {
+    pub fn new(pid: u32, opts: &cargo::Options, lints: Lints) -> Self {
         Self {
             compiled: SList::default(),
-            diagnostics: vec![],
+            tree: SList::default(),
+            view: View::default(),
+            diagnostics: Diagnostics::default(),
+            overview: warnings::Overview::default(),
+            explaining: None,
+            lints,
+            executables: vec![],
+            tests: vec![],
+            bloat: bloat::State::default(),
+            timings: Timings {
+                jobs: cargo::jobs(),
+                ..Timings::default()
+            },
+            started: Instant::now(),
+            pid,
+            cpu: proc::Cpu::default(),
+            last_sample: Instant::now(),
+            passes: opts.passes.clone(),
+            ready: HashMap::new(),
+            began: HashMap::new(),
+            sees_rustc: false,
             crates: vec![],
             failed: false,
-            rx,
+            done: false,
+        }
+    }
+
+    fn sample(&mut self) {
+        if self.last_sample.elapsed() < Duration::from_millis(250) {
+            return;
         }
+        let now = Instant::now();
+        self.last_sample = now;
+        let rustcs = proc::descendants(self.pid)
+            .into_iter()
+            .filter(|&p| proc::stat(p).is_some_and(|s| s.comm == "rustc"))
+            .collect::<Vec<_>>();
+        for &p in &rustcs {
+            if let Some(unit) = compiling(p) {
+                self.sees_rustc = true;
+                self.began.entry(unit).or_insert(now);
+            }
+        }
+        let units = rustcs.len();
+        let Some(cpu) = self.cpu.sample() else { return };
+        self.timings.samples.push(timings::Sample {
+            at: self.started.elapsed().as_secs_f64(),
+            units,
+            cpu,
+        });
+    }
+
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// the test binaries that were built
+    pub fn tests(&self) -> &[TestBinary] {
+        &self.tests
     }
 
-    fn recv(&mut self) -> RecvStatus {
-        let deadline = Instant::now() + Duration::from_millis(50);
-        while let Ok(event) = self.rx.recv_deadline(deadline) {
-            match event {
-                TestMessage::CompilerEvent(e) => match e {
-                    Message::BuildFinished(b) => {
-                        return match b.success {
-                            true => RecvStatus::Finished,
-                            false => RecvStatus::Failed,
+    /// call every frame
+    pub fn tick(&mut self) {
+        if !self.done {
+            self.sample();
+        }
+        self.bloat.recv();
+        self.lints.recv();
+    }
+
+    pub fn handle(&mut self, event: Message, graph: &Graph) {
+        match event {
+            Message::BuildFinished(b) => {
+                self.done = true;
+                self.failed = !b.success;
+                if b.success {
+                    let executables = std::mem::take(&mut self.executables);
+                    self.bloat = bloat::State::new(executables, &graph.meta.target_directory);
+                }
+            }
+            Message::BuildScriptExecuted(f) => {
+                let p = self
+                    .crates
+                    .iter()
+                    .position(|Crate { pid, .. }| pid == &f.package_id)
+                    .unwrap();
+                self.crates[p].state |= BUILD_SCRIPT_EXECUTED;
+                self.ready.insert(f.package_id, Instant::now());
+            }
+            Message::CompilerArtifact(c) => {
+                self.compiled.itemc += 1;
+                if let Some(dir) = self
+                    .passes
+                    .as_deref()
+                    .filter(|_| !c.fresh && graph.is_member(&c.package_id))
+                {
+                    let unit = passes::unit(&c.target.name, &c.target.crate_types, c.profile.test);
+                    if let Some(p) = passes::load(dir, &unit) {
+                        self.timings.passes.push((c.package_id.clone(), unit, p));
+                    }
+                }
+                let now = Instant::now();
+                // fresh ones didnt compile at all
+                if !c.fresh {
+                    let unit = (
+                        c.target.src_path.clone().into_std_path_buf(),
+                        c.profile.test,
+                    );
+                    let start = self.began.remove(&unit).unwrap_or_else(|| {
+                        let ready = self.ready.get(&c.package_id).unwrap_or(&self.started);
+                        match self.sees_rustc {
+                            true => (*ready).max(self.last_sample),
+                            false => *ready,
+                        }
+                    });
+                    self.timings.units.push(timings::Unit {
+                        pid: c.package_id.clone(),
+                        target: c.target.name.clone(),
+                        took: now - start,
+                    });
+                    // so its next unit is counted from here, and they dont overlap
+                    self.ready.insert(c.package_id.clone(), now);
+                }
+                if let Some(path) = c.executable {
+                    let mut name = format!("{} {}", c.target.kind.join(","), c.target.name);
+                    if c.profile.test {
+                        name.insert_str(0, "test ");
+                        if let Some(dir) = c.manifest_path.parent() {
+                            self.tests.push(TestBinary {
+                                path: path.clone(),
+                                dir: dir.into(),
+                                src: c.target.src_path.clone(),
+                            });
                         }
                     }
-                    Message::BuildScriptExecuted(f) => {
-                        let p = self
-                            .crates
-                            .iter()
-                            .position(|Crate { pid, .. }| pid == &f.package_id)
-                            .unwrap();
-                        self.crates[p].state |= BUILD_SCRIPT_EXECUTED;
+                    let key = format!("{} {name}", c.package_id);
+                    self.executables.push(Artifact { name, key, path });
+                }
+                if c.target.name == "build-script-build" {
+                    self.crates.push(Crate {
+                        pid: c.package_id,
+                        state: BUILT_SCRIPT,
+                    });
+                } else {
+                    match self
+                        .crates
+                        .iter()
+                        .position(|Crate { pid, .. }| pid == &c.package_id)
+                    {
+                        None => self.crates.push(Crate {
+                            pid: c.package_id.clone(),
+                            state: FINISHED,
+                        }),
+                        Some(n) => self.crates[n].state |= FINISHED,
                     }
-                    Message::CompilerArtifact(c) => {
-                        self.compiled.itemc += 1;
-                        if c.target.name == "build-script-build" {
-                            self.crates.push(Crate {
-                                pid: c.package_id,
-                                state: BUILT_SCRIPT,
-                            });
-                        } else {
-                            match self
-                                .crates
-                                .iter()
-                                .position(|Crate { pid, .. }| pid == &c.package_id)
-                            {
-                                None => self.crates.push(Crate {
-                                    pid: c.package_id,
-                                    state: FINISHED,
-                                }),
-                                Some(n) => self.crates[n].state |= FINISHED,
-                            }
+                    for d in graph.dependents(&c.package_id) {
+                        let ready = graph
+                            .node(d)
+                            .into_iter()
+                            .flat_map(|n| &n.dependencies)
+                            .all(|p| self.is_finished(p));
+                        if ready {
+                            self.ready.entry(d.clone()).or_insert(now);
                         }
                     }
-                    Message::CompilerMessage(CompilerMessage {
-                        message:
-                            Diagnostic {
-                                rendered: Some(rendered),
-                                ..
-                            },
-                        ..
-                    }) => {
-                        if self.diagnostics.contains(&rendered) {
-                            continue;
-                        };
-                        self.diagnostics.push(rendered);
+                }
+            }
+            Message::CompilerMessage(CompilerMessage {
+                message,
+                package_id,
+                ..
+            }) => {
+                if matches!(message.level, DiagnosticLevel::Error | DiagnosticLevel::Ice) {
+                    match self
+                        .crates
+                        .iter()
+                        .position(|Crate { pid, .. }| pid == &package_id)
+                    {
+                        None => self.crates.push(Crate {
+                            pid: package_id.clone(),
+                            state: FAILED,
+                        }),
+                        Some(n) => self.crates[n].state |= FAILED,
                     }
-                    // Message::CompilerMessage(CompilerMessage { message, .. }) => {
-                    //     let mut h = ahash::AHasher::default();
-                    //     message.hash(&mut h);
-                    //     let v = h.finish();
-                    //     log::trace!("got {message}");
-                    //     if self.diagnostics.iter().all(|&(_, hash)| (hash != v)) {
-                    //         if let Some(span) = message.spans.first() {
-                    //             let f = std::fs::read_to_string(at.join(span.file_name.clone()))
-                    //                 .unwrap();
-                    //             let mut e = lerr::Error::new(&f);
-                    //             e.message(format!(
-                    //                 "{}: {}",
-                    //                 match message.level {
-                    //                     DiagnosticLevel::Help =>
-                    //                         cformat_args!("{green}help{reset}"),
-                    //                     DiagnosticLevel::Note => cformat_args!("{cyan}note{reset}"),
-                    //                     DiagnosticLevel::Warning =>
-                    //                         cformat_args!("{yellow}nit{reset}"),
-                    //                     _ => cformat_args!("{red}error{reset}"),
-                    //                 },
-                    //                 message.message
-                    //             ));
-                    //             for span in message.spans {
-                    //                 e.label((
-                    //                     span.byte_start as usize..span.byte_end as usize,
-                    //                     span.label.unwrap_or("here".to_string()),
-                    //                 ));
-                    //             }
-                    //             self.diagnostics.push((e.to_string(), v));
-                    //             continue;
-                    //         } else {
-                    //             let mut e = lerr::Error::new("\n");
-                    //             e.message(format!(
-                    //                 "{}: {}",
-                    //                 match message.level {
-                    //                     DiagnosticLevel::Help =>
-                    //                         cformat_args!("{green}help{reset}"),
-                    //                     DiagnosticLevel::Note => cformat_args!("{cyan}note{reset}"),
-                    //                     _ => cformat_args!("{red}error{reset}"),
-                    //                 },
-                    //                 message.message
-                    //             ));
-                    //             self.diagnostics.push((e.to_string(), v));
-                    //             continue;
-                    //         }
-                    //     }
-                    _ => {}
-                },
-                e => unreachable!("got bad event {e:?}"),
+                }
+                self.diagnostics.push(package_id, message);
             }
+            // Message::CompilerMessage(CompilerMessage { message, .. }) => {
+            //     let mut h = ahash::AHasher::default();
+            //     message.hash(&mut h);
+            //     let v = h.finish();
+            //     log::trace!("got {message}");
+            //     if self.diagnostics.iter().all(|&(_, hash)| (hash != v)) {
+            //         if let Some(span) = message.spans.first() {
+            //             let f = std::fs::read_to_string(at.join(span.file_name.clone()))
+            //                 .unwrap();
+            //             let mut e = lerr::Error::new(&f);
+            //             e.message(format!(
+            //                 "{}: {}",
+            //                 match message.level {
+            //                     DiagnosticLevel::Help =>
+            //                         cformat_args!("{green}help{reset}"),
+            //                     DiagnosticLevel::Note => cformat_args!("{cyan}note{reset}"),
+            //                     DiagnosticLevel::Warning =>
+            //                         cformat_args!("{yellow}nit{reset}"),
+            //                     _ => cformat_args!("{red}error{reset}"),
+            //                 },
+            //                 message.message
+            //             ));
+            //             for span in message.spans {
+            //                 e.label((
+            //                     span.byte_start as usize..span.byte_end as usize,
+            //                     span.label.unwrap_or("here".to_string()),
+            //                 ));
+            //             }
+            //             self.diagnostics.push((e.to_string(), v));
+            //             continue;
+            //         } else {
+            //             let mut e = lerr::Error::new("\n");
+            //             e.message(format!(
+            //                 "{}: {}",
+            //                 match message.level {
+            //                     DiagnosticLevel::Help =>
+            //                         cformat_args!("{green}help{reset}"),
+            //                     DiagnosticLevel::Note => cformat_args!("{cyan}note{reset}"),
+            //                     _ => cformat_args!("{red}error{reset}"),
+            //                 },
+            //                 message.message
+            //             ));
+            //             self.diagnostics.push((e.to_string(), v));
+            //             continue;
+            //         }
+            //     }
+            _ => {}
         }
-        RecvStatus::None
     }
-}
 
-enum RecvStatus {
-    Finished,
-    Failed,
-    None,
-}
-
-pub fn run<B: Backend>(
-    terminal: &mut Terminal<B>,
-    meta: &cargo::Metadata,
-    rx: Receiver<TestMessage>,
-) -> Result<ControlFlow<(), Receiver<TestMessage>>> {
-    print!("\x1b]0;compiling {}\x07", meta.package.name);
-    let mut state = State::new(rx);
-    loop {
-        terminal.draw(|f| ui::ui(f, &mut state, meta))?;
-        if event::poll(Duration::from_millis(5))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(ControlFlow::Break(())),
-                    KeyCode::Down | KeyCode::Char('s') => state.compiled.next(),
-                    KeyCode::Up | KeyCode::Char('w') => state.compiled.prev(),
-                    _ => {}
+    pub fn key(&mut self, code: KeyCode, graph: &Graph) {
+        if self.view == View::Bloat {
+            match code {
+                KeyCode::Down | KeyCode::Char('s') => {
+                    self.bloat.list.next();
+                    self.bloat.scroll = 0;
+                }
+                KeyCode::Up | KeyCode::Char('w') => {
+                    self.bloat.list.prev();
+                    self.bloat.scroll = 0;
+                }
+                KeyCode::Tab | KeyCode::Char('v') => self.bloat.next_view(),
+                KeyCode::PageDown | KeyCode::Char('j') => self.bloat.scroll_down(),
+                KeyCode::PageUp | KeyCode::Char('k') => {
+                    self.bloat.scroll = self.bloat.scroll.saturating_sub(1)
+                }
+                KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc | KeyCode::Char('b') => {
+                    self.view = View::List
                 }
+                _ => {}
             }
+            return;
         }
-        if state.failed {
-            continue;
+        if self.view == View::Warnings {
+            let o = &mut self.overview;
+            o.note = None;
+            match (code, o.drilled) {
+                (KeyCode::Down | KeyCode::Char('s'), false) => o.groups.next(),
+                (KeyCode::Up | KeyCode::Char('w'), false) => o.groups.prev(),
+                (KeyCode::Down | KeyCode::Char('s'), true) => {
+                    o.diags.next();
+                    o.scroll = 0;
+                }
+                (KeyCode::Up | KeyCode::Char('w'), true) => {
+                    o.diags.prev();
+                    o.scroll = 0;
+                }
+                (KeyCode::Char('?'), true) => {
+                    o.explain = !o.explain;
+                    o.scroll = 0;
+                }
+                (KeyCode::PageDown | KeyCode::Char('j'), true) => o.scroll += 1,
+                (KeyCode::PageUp | KeyCode::Char('k'), true) => {
+                    o.scroll = o.scroll.saturating_sub(1)
+                }
+                (KeyCode::Char('x'), true) => self.overview.note = Some(self.allow(graph)),
+                (KeyCode::Tab | KeyCode::Char('g'), _) => {
+                    o.by = o.by.next();
+                    o.groups.state.select(None);
+                    o.drilled = false;
+                    o.explain = false;
+                }
+                (KeyCode::Right | KeyCode::Char('d') | KeyCode::Enter, false)
+                    if o.groups.state.selected().is_some() =>
+                {
+                    o.drilled = true;
+                    o.scroll = 0;
+                    o.diags.state.select(Some(0));
+                }
+                (KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc, true) if o.explain => {
+                    o.explain = false
+                }
+                (KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc, true) => o.drilled = false,
+                (KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc | KeyCode::Char('l'), _) => {
+                    self.view = View::List
+                }
+                _ => {}
+            }
+            return;
+        }
+        if let Some((_, scroll)) = &mut self.explaining {
+            match code {
+                KeyCode::PageDown | KeyCode::Char('j') => *scroll += 1,
+                KeyCode::PageUp | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc | KeyCode::Char('?') => {
+                    self.explaining = None
+                }
+                _ => {}
+            }
+            return;
+        }
+        match code {
+            // the pane shows the latest at the bottom, so explain that one, errors first
+            KeyCode::Char('?') => {
+                let all = &self.diagnostics.all;
+                self.explaining = all
+                    .iter()
+                    .rev()
+                    .filter(|d| d.is_error())
+                    .chain(all.iter().rev())
+                    .find_map(|d| d.lint.clone())
+                    .map(|lint| (lint, 0));
+            }
+            KeyCode::Char('l') if self.diagnostics.warnings() + self.diagnostics.errors() != 0 => {
+                self.view = View::Warnings
+            }
+            KeyCode::Char('t') => {
+                self.view = match self.view {
+                    View::Tree => View::List,
+                    _ => View::Tree,
+                }
+            }
+            KeyCode::Char('p') => {
+                self.view = match self.view {
+                    View::Timings => View::List,
+                    _ => View::Timings,
+                }
+            }
+            KeyCode::Char('b') if self.done && !self.failed => self.view = View::Bloat,
+            KeyCode::Down | KeyCode::Char('s') => match self.view {
+                View::List => self.compiled.next(),
+                View::Tree => self.tree.next(),
+                View::Timings | View::Bloat | View::Warnings => {}
+            },
+            KeyCode::Up | KeyCode::Char('w') => match self.view {
+                View::List => self.compiled.prev(),
+                View::Tree => self.tree.prev(),
+                View::Timings | View::Bloat | View::Warnings => {}
+            },
+            _ => {}
         }
-        match state.recv() {
-            RecvStatus::Failed => state.failed = true,
-            RecvStatus::None => {}
-            RecvStatus::Finished => return Ok(ControlFlow::Continue(state.rx)),
+    }
+
+    /// the warning selected in the overview
+    fn selected_warning(&self, graph: &Graph) -> Option<&warnings::Diag> {
+        let o = &self.overview;
+        let groups = self.diagnostics.groups(o.by, graph, &self.lints);
+        let (_, diags) = groups.get(o.groups.state.selected()?)?;
+        Some(&self.diagnostics.all[*diags.get(o.diags.state.selected()?)?])
+    }
+
+    /// toggles allowing the selected warnings lint in its crate
+    fn allow(&self, graph: &Graph) -> String {
+        let Some(d) = self.selected_warning(graph) else {
+            return "nothing selected".to_string();
+        };
+        let Some(lint) = &d.lint else {
+            return "thats not a lint".to_string();
+        };
+        let Some(p) = graph.package(&d.pid).filter(|_| graph.is_member(&d.pid)) else {
+            return format!("{} isnt in the workspace", graph.name(&d.pid));
         };
+        match lints::toggle_allow(p.manifest_path.as_std_path(), lint) {
+            Ok(true) => format!("allowed {lint} in {}, rebuild to see", p.name),
+            Ok(false) => format!("stopped allowing {lint} in {}", p.name),
+            Err(e) => format!("couldnt allow {lint}: {e}"),
+        }
+    }
+
+    fn is_finished(&self, pid: &PackageId) -> bool {
+        self.crates
+            .iter()
+            .any(|c| &c.pid == pid && c.state & FINISHED != 0)
+    }
+
+    fn selected<'a>(&'a self, graph: &'a Graph) -> Option<&'a PackageId> {
+        match self.view {
+            View::List => self
+                .crates
+                .get(self.compiled.state.selected()?)
+                .map(|c| &c.pid),
+            View::Tree => graph.tree.get(self.tree.state.selected()?).map(|r| &r.pid),
+            View::Timings | View::Bloat | View::Warnings => None,
+        }
+    }
+
+    /// everything downstream of a failed crate
+    fn blocked(&self, graph: &Graph) -> HashSet<PackageId> {
+        let mut blocked = HashSet::new();
+        for c in self.crates.iter().filter(|c| c.state & FAILED != 0) {
+            graph.blocked_by(&c.pid, &mut blocked);
+        }
+        blocked
+    }
+
+    fn status(&self, graph: &Graph, blocked: &HashSet<PackageId>, pid: &PackageId) -> Status {
+        let state = |pid| {
+            self.crates
+                .iter()
+                .find(|c| &c.pid == pid)
+                .map_or(0, |c| c.state)
+        };
+        match state(pid) {
+            s if s & FAILED != 0 => Status::Failed,
+            s if s & FINISHED != 0 => Status::Built,
+            _ if blocked.contains(pid) => Status::Blocked,
+            0 if self.failed => Status::Waiting,
+            // cargo doesnt say when it starts a unit, so guess from the deps
+            0 if graph
+                .node(pid)
+                .into_iter()
+                .flat_map(|n| &n.dependencies)
+                .any(|d| state(d) & FINISHED == 0) =>
+            {
+                Status::Waiting
+            }
+            _ => Status::Building,
+        }
     }
 }
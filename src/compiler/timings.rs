@@ -0,0 +1,69 @@
+//! how long each unit took to compile
+use crate::passes::Passes;
+use cargo_metadata::PackageId;
+use std::{collections::HashMap, time::Duration};
+
+/// histogram buckets, in seconds
+const BUCKETS: [(&str, f32); 8] = [
+    ("<.1s", 0.1),
+    ("<.5s", 0.5),
+    ("<1s", 1.),
+    ("<2s", 2.),
+    ("<5s", 5.),
+    ("<10s", 10.),
+    ("<30s", 30.),
+    ("30s+", f32::INFINITY),
+];
+
+pub struct Unit {
+    pub pid: PackageId,
+    pub target: String,
+    pub took: Duration,
+}
+
+pub struct Sample {
+    /// seconds since the build started
+    pub at: f64,
+    /// rustcs running
+    pub units: usize,
+    /// cpu utilization, 0..1
+    pub cpu: f64,
+}
+
+#[derive(Default)]
+pub struct Timings {
+    pub units: Vec<Unit>,
+    pub samples: Vec<Sample>,
+    /// the `-j`
+    pub jobs: usize,
+    /// `-Ztime-passes` of workspace units
+    pub passes: Vec<(PackageId, String, Passes)>,
+}
+
+impl Timings {
+    /// (crate, total time, unit count), slowest first
+    pub fn crates(&self) -> Vec<(&PackageId, Duration, usize)> {
+        let mut crates = HashMap::<_, (Duration, usize)>::new();
+        for Unit { pid, took, .. } in &self.units {
+            let c = crates.entry(pid).or_default();
+            c.0 += *took;
+            c.1 += 1;
+        }
+        let mut crates = crates
+            .into_iter()
+            .map(|(pid, (took, n))| (pid, took, n))
+            .collect::<Vec<_>>();
+        crates.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        crates
+    }
+
+    pub fn histogram(&self) -> Vec<(&'static str, u64)> {
+        let mut h = BUCKETS.map(|(name, _)| (name, 0));
+        for u in &self.units {
+            let secs = u.took.as_secs_f32();
+            let i = BUCKETS.iter().position(|&(_, max)| secs < max).unwrap();
+            h[i].1 += 1;
+        }
+        h.to_vec()
+    }
+}
@@ -1,19 +1,36 @@
-use super::Crate;
-use super::FINISHED;
+use super::{Crate, Status, View};
 use crate::cargo;
+use crate::graph::Graph;
 use crate::ui::*;
+use cargo_metadata::PackageId;
+use std::collections::HashSet;
+mod package;
+pub mod timings;
+mod tree;
+mod warnings;
 
-pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut super::State, meta: &cargo::Metadata) {
+pub fn ui<B: Backend>(
+    f: &mut Frame<B>,
+    state: &mut super::State,
+    meta: &cargo::Metadata,
+    graph: &Graph,
+    chunk: Rect,
+) {
     let chunks = Layout::default()
         .direction(Vertical)
         .constraints([Length(3), Min(1), Length(1)])
-        .split(f.size());
+        .split(chunk);
     f.render_widget(
         if state.failed {
             Paragraph::new(ctext!(
                 "{green}compiling {:bold_red}{reset}",
                 meta.package.name
             ))
+        } else if state.done {
+            Paragraph::new(ctext!(
+                "{green}built {:bold_cyan}{reset}",
+                meta.package.name
+            ))
         } else {
             Paragraph::new(ctext!(
                 "{green}compiling {:bold_cyan}{reset}",
@@ -28,51 +45,145 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut super::State, meta: &cargo::
         ),
         chunks[0],
     );
-    let mut l = Vec::with_capacity(state.crates.len());
-    for Crate { state, pid, .. } in &state.crates {
-        let name = pid.repr.split(' ').next().unwrap();
-        if state & FINISHED != 0 {
-            l.pt(ctext!("{green}built    {:blue}", name));
-        } else {
-            l.pt(ctext!("{yellow}building {:blue}", name));
-        }
-    }
-    let l = List::new(l)
-        .highlight_style(Style::default().on_light_green().italic())
-        .highlight_symbol("> ")
-        .block(Block::default().borders(Borders::ALL));
-    if state.diagnostics.is_empty() {
-        f.render_stateful_widget(l, chunks[1], &mut state.compiled.state);
+    let blocked = state.blocked(graph);
+    let selected = state.selected(graph).cloned();
+    if state.view == View::Warnings {
+        warnings::warnings(
+            f,
+            &state.diagnostics,
+            &mut state.overview,
+            &mut state.lints,
+            graph,
+            chunks[1],
+        );
+    } else if state.diagnostics.is_empty() && selected.is_none() {
+        left(f, state, graph, &blocked, chunks[1]);
     } else {
         let chunks = Layout::default()
             .direction(Horizontal)
             .constraints([Percentage(60), Percentage(40)])
             .split(chunks[1]);
-        f.render_stateful_widget(l, chunks[0], &mut state.compiled.state);
-        let o = state.diagnostics.concat();
-        let lines = o.lines().count() as u16;
-        f.render_widget(
-            Paragraph::new(o)
-                .scroll((lines.saturating_sub(chunks[1].height), 0))
-                .block(Block::default().title("diagnostics").borders(Borders::ALL)),
-            chunks[1],
-        );
+        let side = match (&selected, state.diagnostics.is_empty()) {
+            (Some(pid), true) => {
+                package::package(f, graph, pid, chunks[1]);
+                None
+            }
+            (Some(pid), false) => {
+                let side = Layout::default()
+                    .direction(Vertical)
+                    .constraints([Percentage(50), Percentage(50)])
+                    .split(chunks[1]);
+                package::package(f, graph, pid, side[0]);
+                Some(side[1])
+            }
+            (None, _) => Some(chunks[1]),
+        };
+        if let Some((side, (lint, scroll))) = side.zip(state.explaining.as_ref()) {
+            warnings::explain(f, lint, &mut state.lints, *scroll, side);
+        } else if let Some(side) = side {
+            let mut o = String::new();
+            for d in &state.diagnostics.all {
+                o.push_str(&d.rendered);
+                if d.count > 1 {
+                    o.push_str(&format!("(reported {} times)\n", d.count));
+                }
+            }
+            let lines = o.lines().count() as u16;
+            f.render_widget(
+                Paragraph::new(o)
+                    .scroll((lines.saturating_sub(side.height), 0))
+                    .block(Block::default().title("diagnostics").borders(Borders::ALL)),
+                side,
+            );
+        }
+        left(f, state, graph, &blocked, chunks[0]);
     }
 
     let footer_chunks = Layout::default()
         .direction(Horizontal)
         .constraints([Percentage(50), Percentage(50)])
         .split(chunks[2]);
-    let usage = Paragraph::new(ctext!(
-        "press {green}up{reset} or {red}down{reset} to change selection"
-    ));
+    let usage = match state.view {
+        View::Bloat => Paragraph::new(ctext!(
+            "press {blue}left{reset} to go back to crates | press {green}tab{reset} to switch breakdown | press {green}j{reset} or {red}k{reset} to scroll"
+        )),
+        View::Warnings if state.overview.explain => Paragraph::new(ctext!(
+            "press {blue}left{reset} or {blue}?{reset} to go back | press {green}j{reset} or {red}k{reset} to scroll"
+        )),
+        View::Warnings if state.overview.drilled => Paragraph::new(ctext!(
+            "press {blue}left{reset} to go back | press {blue}?{reset} to explain | press {green}j{reset} or {red}k{reset} to scroll | press {red}x{reset} to allow"
+        )),
+        View::Warnings => Paragraph::new(ctext!(
+            "press {blue}left{reset} to go back to crates | press {green}tab{reset} to group by lint, lint group or crate | press {green}right{reset} to drill down"
+        )),
+        _ if state.explaining.is_some() => Paragraph::new(ctext!(
+            "press {blue}left{reset} or {blue}?{reset} to go back | press {green}j{reset} or {red}k{reset} to scroll"
+        )),
+        _ if state.done && state.diagnostics.warnings() + state.diagnostics.errors() != 0 => Paragraph::new(ctext!(
+            "press {blue}t{reset} for the dependency tree | press {blue}p{reset} for compile times | press {blue}l{reset} for warnings | press {blue}?{reset} to explain the last"
+        )),
+        _ if state.done && !state.failed => Paragraph::new(ctext!(
+            "press {blue}t{reset} for the dependency tree | press {blue}p{reset} for compile times | press {blue}b{reset} for binary sizes"
+        )),
+        _ => Paragraph::new(ctext!(
+            "press {green}up{reset} or {red}down{reset} to change selection | press {blue}t{reset} to toggle the dependency tree | press {blue}p{reset} for compile times"
+        )),
+    };
     f.render_widget(usage, footer_chunks[0]);
-    let status = match (|| state.crates.get(state.compiled.state.selected()?))() {
-        Some(c) => Paragraph::new(ctext!(
-            "viewing crate {:blue}",
-            c.pid.repr.split(' ').next().unwrap()
+    let status = match (&state.view, selected) {
+        (View::Bloat, _) => match state.bloat.selects() {
+            Some((a, _)) => Paragraph::new(ctext!("viewing size of {:blue}", a.name)),
+            None => Paragraph::new("listing executables"),
+        },
+        (View::Timings, _) => Paragraph::new("viewing compile times"),
+        (View::Warnings, _) if state.overview.note.is_some() => {
+            Paragraph::new(state.overview.note.as_deref().unwrap())
+        }
+        (View::Warnings, _) => Paragraph::new(ctext!(
+            "{:yellow} warnings, {:red} errors, {} repeats",
+            state.diagnostics.warnings(),
+            state.diagnostics.errors(),
+            state.diagnostics.repeats()
         )),
-        None => Paragraph::new("listing crates"),
+        (_, Some(pid)) => Paragraph::new(ctext!("viewing crate {:blue}", graph.name(&pid))),
+        (_, None) => Paragraph::new("listing crates"),
     };
     f.render_widget(status, footer_chunks[1]);
 }
+
+fn left<B: Backend>(
+    f: &mut Frame<B>,
+    state: &mut super::State,
+    graph: &Graph,
+    blocked: &HashSet<PackageId>,
+    chunk: Rect,
+) {
+    match state.view {
+        View::Tree => return tree::tree(f, state, graph, blocked, chunk),
+        View::Timings => return timings::timings(f, &state.timings, graph, chunk),
+        View::Bloat => return crate::bloat::ui::bloat(f, &mut state.bloat, chunk),
+        // takes up the whole screen
+        View::Warnings => return,
+        View::List => {}
+    }
+    let chunks = Layout::default()
+        .direction(Vertical)
+        .constraints([Percentage(65), Percentage(35)])
+        .split(chunk);
+    timings::concurrency(f, &state.timings, chunks[1]);
+    let chunk = chunks[0];
+    let mut l = Vec::with_capacity(state.crates.len());
+    for Crate { pid, .. } in &state.crates {
+        let name = graph.name(pid);
+        match state.status(graph, blocked, pid) {
+            Status::Built => l.pt(ctext!("{green}built    {:blue}", name)),
+            Status::Failed => l.pt(ctext!("{bold_red}failed   {:blue}", name)),
+            _ => l.pt(ctext!("{yellow}building {:blue}", name)),
+        }
+    }
+    let l = List::new(l)
+        .highlight_style(Style::default().on_light_green().italic())
+        .highlight_symbol("> ")
+        .block(Block::default().borders(Borders::ALL));
+    f.render_stateful_widget(l, chunk, &mut state.compiled.state);
+}
@@ -0,0 +1,78 @@
+use crate::graph::{self, Graph};
+use crate::ui::*;
+use cargo_metadata::PackageId;
+
+pub fn package<B: Backend>(f: &mut Frame<B>, graph: &Graph, pid: &PackageId, chunk: Rect) {
+    let b = Block::default().title("package").borders(Borders::ALL);
+    let Some(p) = graph.package(pid) else {
+        f.render_widget(
+            Paragraph::new(ctext!("{:bold_yellow} is not in the resolve", pid.repr))
+                .block(b)
+                .wrap(Wrap { trim: true }),
+            chunk,
+        );
+        return;
+    };
+    let none = || "none".italic();
+    let mut t = vec![
+        Line::from(vec![
+            p.name.as_str().bold().blue(),
+            " ".into(),
+            Span::styled(format!("v{}", p.version), Style::default().green()),
+        ]),
+        field(
+            "source      ",
+            Span::styled(graph::source(p), Style::default().cyan()),
+        ),
+        field(
+            "license     ",
+            p.license.as_deref().map_or_else(none, Stylize::yellow),
+        ),
+        field(
+            "repository  ",
+            p.repository
+                .as_deref()
+                .map_or_else(none, Stylize::underlined),
+        ),
+    ];
+    if let Some(d) = &p.description {
+        t.push(Line::default());
+        t.push(Line::from(d.trim().italic()));
+    }
+    t.push(Line::default());
+    let features = graph.features(pid);
+    if features.is_empty() {
+        t.push(field("features    ", none()));
+    } else {
+        t.push(field(
+            "features    ",
+            Span::styled(features.join(", "), Style::default().magenta()),
+        ));
+    }
+    let names = |pids: &[PackageId]| {
+        let mut v = pids.iter().map(|p| graph.name(p)).collect::<Vec<_>>();
+        v.sort_unstable();
+        v.dedup();
+        v.join(", ")
+    };
+    if graph.is_member(pid) {
+        t.push(Line::from("workspace member".bold().green()));
+    } else {
+        t.push(field(
+            "needed by   ",
+            Span::styled(names(graph.users(pid)), Style::default().bold().green()),
+        ));
+    }
+    let dependents = graph.dependents(pid);
+    if !dependents.is_empty() {
+        t.push(field(
+            "through     ",
+            Span::styled(names(dependents), Style::default().blue()),
+        ));
+    }
+    f.render_widget(Paragraph::new(t).block(b).wrap(Wrap { trim: false }), chunk);
+}
+
+fn field<'a>(name: &'static str, v: Span<'a>) -> Line<'a> {
+    Line::from(vec![name.into(), v])
+}
@@ -0,0 +1,191 @@
+use crate::compiler::Timings;
+use crate::graph::Graph;
+use crate::ui::*;
+use ratatui::{
+    symbols::Marker,
+    widgets::{Axis, BarChart, Chart, Dataset, GraphType},
+};
+
+pub fn timings<B: Backend>(f: &mut Frame<B>, timings: &Timings, graph: &Graph, chunk: Rect) {
+    let chunks = Layout::default()
+        .direction(Vertical)
+        .constraints([Percentage(60), Percentage(40)])
+        .split(chunk);
+    let top = if timings.passes.is_empty() {
+        [chunks[0]].into()
+    } else {
+        Layout::default()
+            .direction(Horizontal)
+            .constraints([Percentage(50), Percentage(50)])
+            .split(chunks[0])
+    };
+    if let Some(&chunk) = top.get(1) {
+        passes(f, timings, graph, chunk);
+    }
+    let crates = timings.crates();
+    let slowest = crates.first().map_or(1., |c| c.1.as_secs_f32());
+    let mut l = Vec::with_capacity(crates.len());
+    for (i, (pid, took, units)) in crates.iter().enumerate() {
+        let secs = took.as_secs_f32();
+        l.pl(vec![
+            Span::styled(format!("{:>3}. ", i + 1), Style::default().dark_gray()),
+            Span::styled(format!("{secs:>7.2}s "), Style::default().bold().yellow()),
+            Span::styled(
+                "█".repeat((secs / slowest * 20.).ceil() as usize),
+                Style::default().red(),
+            ),
+            Span::styled(format!(" {}", graph.name(pid)), Style::default().blue()),
+            Span::styled(
+                format!(" ({units} unit{})", if *units == 1 { "" } else { "s" }),
+                Style::default().dark_gray(),
+            ),
+        ]);
+    }
+    f.render_widget(
+        List::new(l).block(
+            Block::default()
+                .title("slowest crates")
+                .borders(Borders::ALL),
+        ),
+        top[0],
+    );
+    let bottom = Layout::default()
+        .direction(Horizontal)
+        .constraints([Percentage(50), Percentage(50)])
+        .split(chunks[1]);
+    let histogram = timings.histogram();
+    f.render_widget(
+        BarChart::default()
+            .data(&histogram)
+            .bar_width(6)
+            .bar_gap(2)
+            .bar_style(Style::default().cyan())
+            .value_style(Style::default().black().on_cyan())
+            .block(
+                Block::default()
+                    .title("unit compile times")
+                    .borders(Borders::ALL),
+            ),
+        bottom[0],
+    );
+    concurrency(f, timings, bottom[1]);
+}
+
+const PASS_COLORS: [Color; 8] = [
+    Color::Blue,
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Red,
+    Color::LightBlue,
+    Color::DarkGray,
+];
+
+/// where the time went in each workspace unit, from `-Ztime-passes`
+fn passes<B: Backend>(f: &mut Frame<B>, timings: &Timings, graph: &Graph, chunk: Rect) {
+    let width = chunk.width.saturating_sub(2) as f64;
+    let longest = timings
+        .passes
+        .iter()
+        .map(|(_, _, p)| p.total)
+        .fold(f64::EPSILON, f64::max);
+    let mut l = vec![];
+    let legend = timings.passes[0].2.breakdown();
+    l.pl(legend
+        .iter()
+        .zip(PASS_COLORS)
+        .map(|(&(name, _), c)| Span::styled(format!("█ {name} "), Style::default().fg(c)))
+        .collect::<Vec<_>>());
+    for (pid, unit, p) in &timings.passes {
+        l.pl(vec![
+            Span::styled(graph.name(pid).to_string(), Style::default().bold().blue()),
+            Span::styled(
+                format!(" {}", unit.split_once(' ').map_or("", |(_, kind)| kind)),
+                Style::default().dark_gray(),
+            ),
+            Span::styled(format!(" {:.2}s", p.total), Style::default().yellow()),
+        ]);
+        let mut bar = vec![];
+        let mut details = vec![];
+        for (&(name, secs), c) in p.breakdown().iter().zip(PASS_COLORS) {
+            let n = (secs / longest * width).round() as usize;
+            bar.push(Span::styled("█".repeat(n), Style::default().fg(c)));
+            if secs >= 0.005 {
+                details.push(Span::styled(
+                    format!("{name} {secs:.2}s "),
+                    Style::default().fg(c),
+                ));
+            }
+        }
+        l.pl(bar);
+        l.pl(details);
+    }
+    f.render_widget(
+        List::new(l).block(Block::default().title("rustc passes").borders(Borders::ALL)),
+        chunk,
+    );
+}
+
+/// rustcs in flight and cpu usage over time, against `-j`
+pub fn concurrency<B: Backend>(f: &mut Frame<B>, timings: &Timings, chunk: Rect) {
+    let jobs = timings.jobs as f64;
+    let units = timings
+        .samples
+        .iter()
+        .map(|s| (s.at, s.units as f64))
+        .collect::<Vec<_>>();
+    // scaled so that all cores busy == -j
+    let cpu = timings
+        .samples
+        .iter()
+        .map(|s| (s.at, s.cpu * jobs))
+        .collect::<Vec<_>>();
+    // one crate compiling while the cores sit idle
+    let serial = timings
+        .samples
+        .iter()
+        .filter(|s| s.units == 1 && s.cpu * jobs < 1.5)
+        .map(|s| (s.at, 1.))
+        .collect::<Vec<_>>();
+    let end = timings.samples.last().map_or(1., |s| s.at.max(1.));
+    let datasets = vec![
+        Dataset::default()
+            .name("cpu")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().cyan())
+            .data(&cpu),
+        Dataset::default()
+            .name("rustc")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().yellow())
+            .data(&units),
+        Dataset::default()
+            .name("serial")
+            .marker(Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().red())
+            .data(&serial),
+    ];
+    let title = Line::from(vec![
+        "concurrency ".into(),
+        Span::styled(format!("-j{}", timings.jobs), Style::default().bold()),
+    ]);
+    f.render_widget(
+        Chart::new(datasets)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .x_axis(
+                Axis::default()
+                    .bounds([0., end])
+                    .labels(vec!["0s".into(), Span::raw(format!("{end:.0}s"))]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0., jobs])
+                    .labels(vec!["0".into(), Span::raw(format!("{jobs}"))]),
+            ),
+        chunk,
+    );
+}
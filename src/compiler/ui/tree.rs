@@ -0,0 +1,55 @@
+use super::super::{State, Status};
+use crate::graph::{Graph, Row};
+use crate::ui::*;
+use cargo_metadata::PackageId;
+use std::collections::HashSet;
+
+pub fn tree<B: Backend>(
+    f: &mut Frame<B>,
+    state: &mut State,
+    graph: &Graph,
+    blocked: &HashSet<PackageId>,
+    chunk: Rect,
+) {
+    let mut l = Vec::with_capacity(graph.tree.len());
+    for Row { depth, pid, repeat } in &graph.tree {
+        let mut line = vec![Span::raw("  ".repeat(*depth))];
+        let name = graph.name(pid);
+        line.push(match state.status(graph, blocked, pid) {
+            Status::Waiting => name.dark_gray(),
+            Status::Building => name.yellow(),
+            Status::Built => name.green(),
+            Status::Failed => name.bold().red(),
+            Status::Blocked => name.italic().light_red(),
+        });
+        if let Some(p) = graph.package(pid) {
+            line.push(Span::styled(
+                format!(" v{}", p.version),
+                Style::default().dark_gray(),
+            ));
+        }
+        if *repeat {
+            line.push(" (*)".dark_gray());
+        }
+        if graph.is_duplicate(pid) {
+            line.push(" duplicate".bold().magenta());
+        }
+        if blocked.contains(pid) {
+            line.push(" blocked".italic().light_red());
+        }
+        l.pl(line);
+    }
+    state.tree.has(l.len());
+    f.render_stateful_widget(
+        List::new(l)
+            .highlight_style(Style::default().on_light_green().italic())
+            .highlight_symbol("> ")
+            .block(
+                Block::default()
+                    .title("dependency tree")
+                    .borders(Borders::ALL),
+            ),
+        chunk,
+        &mut state.tree.state,
+    );
+}
@@ -0,0 +1,225 @@
+use crate::compiler::warnings::{By, Diagnostics, Overview};
+use crate::graph::Graph;
+use crate::lints::{Explained, Lints};
+use crate::ui::{markdown, *};
+
+pub fn warnings<B: Backend>(
+    f: &mut Frame<B>,
+    diagnostics: &Diagnostics,
+    o: &mut Overview,
+    lints: &mut Lints,
+    graph: &Graph,
+    chunk: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Horizontal)
+        .constraints([Percentage(40), Percentage(60)])
+        .split(chunk);
+    let groups = diagnostics.groups(o.by, graph, lints);
+    o.groups.has(groups.len());
+    let most = groups.first().map_or(1, |g| g.1.len());
+    let mut l = Vec::with_capacity(groups.len());
+    for (name, diags) in &groups {
+        let n = diags.len();
+        let color = if diags.iter().any(|&i| diagnostics.all[i].is_error()) {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        l.pl(vec![
+            Span::styled(format!("{n:>4} "), Style::default().bold().fg(color)),
+            Span::styled(
+                "█".repeat((n as f32 / most as f32 * 20.).ceil() as usize),
+                Style::default().fg(color),
+            ),
+            Span::styled(format!(" {name}"), Style::default().blue()),
+        ]);
+    }
+    let title = match o.by {
+        By::Lint => "warnings by lint",
+        By::LintGroup => "warnings by lint group",
+        By::Crate => "warnings by crate",
+    };
+    f.render_stateful_widget(
+        List::new(l)
+            .highlight_style(Style::default().on_light_green().italic())
+            .highlight_symbol("> ")
+            .block(
+                Block::default()
+                    .title(match diagnostics.errors() {
+                        0 => format!("{title} ({})", diagnostics.warnings()),
+                        e => format!("{title} ({}, {e} errors)", diagnostics.warnings()),
+                    })
+                    .borders(Borders::ALL),
+            ),
+        chunks[0],
+        &mut o.groups.state,
+    );
+    let side = Layout::default()
+        .direction(Vertical)
+        .constraints(if o.drilled {
+            [Percentage(30), Percentage(35), Percentage(35)].as_slice()
+        } else {
+            [Percentage(40), Percentage(60)].as_slice()
+        })
+        .split(chunks[1]);
+    let Some((name, diags)) = o.groups.state.selected().and_then(|i| groups.get(i)) else {
+        f.render_widget(
+            Paragraph::new("select a group to see its warnings")
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL)),
+            chunks[1],
+        );
+        return;
+    };
+    o.diags.has(diags.len());
+    let selected = o
+        .diags
+        .state
+        .selected()
+        .filter(|_| o.drilled)
+        .and_then(|i| diags.get(i))
+        .map(|&i| &diagnostics.all[i]);
+    if let Some(lint) = selected
+        .and_then(|d| d.lint.as_deref())
+        .filter(|_| o.explain)
+    {
+        return explain(f, lint, lints, o.scroll, chunks[1]);
+    }
+    let mut l = Vec::with_capacity(diags.len());
+    for d in diags.iter().map(|&i| &diagnostics.all[i]) {
+        // the other grouping, as the group is the same for all of them
+        let tag = match o.by {
+            By::Lint => graph.name(&d.pid).to_string(),
+            By::LintGroup | By::Crate => d.lint.clone().unwrap_or_default(),
+        };
+        let mut line = vec![
+            Span::styled(
+                format!("{} ", d.at.as_deref().unwrap_or("")),
+                Style::default().dark_gray(),
+            ),
+            Span::raw(d.message.as_str()),
+            Span::styled(format!(" {tag}"), Style::default().blue()),
+        ];
+        if d.count > 1 {
+            line.push(Span::styled(
+                format!(" ×{}", d.count),
+                Style::default().yellow(),
+            ));
+        }
+        l.pl(line);
+    }
+    let list = List::new(l).block(Block::default().title(name.as_str()).borders(Borders::ALL));
+    if o.drilled {
+        f.render_stateful_widget(
+            list.highlight_style(Style::default().on_light_green().italic())
+                .highlight_symbol("> "),
+            side[0],
+            &mut o.diags.state,
+        );
+    } else {
+        f.render_widget(list, side[0]);
+    }
+    let b = Block::default().title("diagnostic").borders(Borders::ALL);
+    match selected {
+        Some(d) => {
+            f.render_widget(Paragraph::new(d.rendered.as_str()).block(b), side[1]);
+            lint(f, d.lint.as_deref(), lints, o.scroll, side[2]);
+        }
+        None => f.render_widget(
+            Paragraph::new(ctext!("press {green}right{reset} to look through them"))
+                .alignment(Alignment::Center)
+                .block(b),
+            side[1],
+        ),
+    }
+}
+
+fn lint<B: Backend>(
+    f: &mut Frame<B>,
+    lint: Option<&str>,
+    lints: &mut Lints,
+    scroll: u16,
+    chunk: Rect,
+) {
+    let b = Block::default().title("lint").borders(Borders::ALL);
+    let Some(lint) = lint else {
+        f.render_widget(Paragraph::new("not from a lint".italic()).block(b), chunk);
+        return;
+    };
+    let mut t = vec![Line::from(vec![
+        Span::styled(lint, Style::default().bold().yellow()),
+        match lints.group(lint) {
+            Some(g) => Span::styled(format!(" in {g}"), Style::default().blue()),
+            None => "".into(),
+        },
+    ])];
+    t.push(Line::from(Span::styled(
+        lints.link(lint),
+        Style::default().underlined().cyan(),
+    )));
+    if let Some(desc) = lints.description(lint) {
+        t.push(Line::from(Span::styled(
+            desc.to_string(),
+            Style::default().italic(),
+        )));
+    }
+    match lints.explain(lint) {
+        Explained::Text(e) => {
+            t.push(Line::styled(
+                "press ? to read it all",
+                Style::default().dark_gray().italic(),
+            ));
+            t.push(Line::default());
+            t.extend(markdown::markdown(e));
+        }
+        Explained::Loading => t.push(Line::styled(
+            "loading…",
+            Style::default().dark_gray().italic(),
+        )),
+        Explained::Missing => {}
+    }
+    f.render_widget(
+        Paragraph::new(t)
+            .scroll((scroll, 0))
+            .wrap(Wrap { trim: false })
+            .block(b),
+        chunk,
+    );
+}
+
+/// the whole `--explain`
+pub fn explain<B: Backend>(
+    f: &mut Frame<B>,
+    lint: &str,
+    lints: &mut Lints,
+    scroll: u16,
+    chunk: Rect,
+) {
+    let title = match lint.strip_prefix("clippy::") {
+        Some(name) => format!("cargo clippy --explain {name}"),
+        None => format!("rustc --explain {lint}"),
+    };
+    let b = Block::default().title(title).borders(Borders::ALL);
+    match lints.explain(lint) {
+        Explained::Text(e) => f.render_widget(
+            Paragraph::new(markdown::markdown(e))
+                .scroll((scroll, 0))
+                .wrap(Wrap { trim: false })
+                .block(b),
+            chunk,
+        ),
+        Explained::Loading => f.render_widget(
+            Paragraph::new("loading…".italic())
+                .alignment(Alignment::Center)
+                .block(b),
+            chunk,
+        ),
+        Explained::Missing => f.render_widget(
+            Paragraph::new(ctext!("theres no explanation for {:yellow}", lint))
+                .alignment(Alignment::Center)
+                .block(b),
+            chunk,
+        ),
+    }
+}
@@ -0,0 +1,168 @@
+//! diagnostics, deduplicated, and warnings grouped for an overview
+use cargo_metadata::{
+    diagnostic::{Diagnostic, DiagnosticLevel},
+    PackageId,
+};
+use std::collections::HashMap;
+
+use crate::{graph::Graph, lints::Lints, ui::SList};
+
+/// what makes two diagnostics the same.
+/// the lib and test targets of a crate both report everything in the shared files.
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+    level: DiagnosticLevel,
+    lint: Option<String>,
+    message: String,
+    /// file, line, column of the primary span
+    at: Option<(String, usize, usize)>,
+}
+
+pub struct Diag {
+    pub pid: PackageId,
+    pub level: DiagnosticLevel,
+    /// `unused_variables`, `clippy::needless_borrow`, `E0308`..
+    pub lint: Option<String>,
+    pub message: String,
+    /// `src/lib.rs:4:9`
+    pub at: Option<String>,
+    pub rendered: String,
+    /// times it was reported
+    pub count: usize,
+}
+
+impl Diag {
+    /// not counting the `n warnings emitted` summaries
+    pub fn is_warning(&self) -> bool {
+        self.level == DiagnosticLevel::Warning && !self.is_summary()
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self.level, DiagnosticLevel::Error | DiagnosticLevel::Ice) && !self.is_summary()
+    }
+
+    fn is_summary(&self) -> bool {
+        self.lint.is_none() && self.at.is_none()
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum By {
+    #[default]
+    Lint,
+    /// `clippy::style`, `unused`..
+    LintGroup,
+    Crate,
+}
+
+impl By {
+    pub fn next(self) -> Self {
+        match self {
+            By::Lint => By::LintGroup,
+            By::LintGroup => By::Crate,
+            By::Crate => By::Lint,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Diagnostics {
+    pub all: Vec<Diag>,
+    seen: HashMap<Key, usize>,
+}
+
+impl Diagnostics {
+    /// false if it was a repeat
+    pub fn push(&mut self, pid: PackageId, d: Diagnostic) -> bool {
+        let Some(rendered) = d.rendered else {
+            return false;
+        };
+        let lint = d.code.map(|c| c.code);
+        let span = d.spans.iter().find(|s| s.is_primary);
+        let key = Key {
+            level: d.level,
+            lint: lint.clone(),
+            message: d.message.clone(),
+            at: span.map(|s| (s.file_name.clone(), s.line_start, s.column_start)),
+        };
+        if let Some(&i) = self.seen.get(&key) {
+            self.all[i].count += 1;
+            return false;
+        }
+        self.seen.insert(key, self.all.len());
+        self.all.push(Diag {
+            pid,
+            level: d.level,
+            lint,
+            message: d.message,
+            at: span.map(|s| format!("{}:{}:{}", s.file_name, s.line_start, s.column_start)),
+            rendered,
+            count: 1,
+        });
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all.is_empty()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.all.iter().filter(|d| d.is_warning()).count()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.all.iter().filter(|d| d.is_error()).count()
+    }
+
+    /// warnings that were reported more than once, past the first time
+    pub fn repeats(&self) -> usize {
+        self.all
+            .iter()
+            .filter(|d| d.is_warning())
+            .map(|d| d.count - 1)
+            .sum()
+    }
+
+    /// (group name, indices of its warnings and errors), biggest first
+    pub fn groups(&self, by: By, graph: &Graph, lints: &Lints) -> Vec<(String, Vec<usize>)> {
+        let mut groups = HashMap::<_, Vec<_>>::new();
+        for (i, d) in self
+            .all
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_warning() || d.is_error())
+        {
+            let name = match by {
+                By::Lint => d.lint.clone().unwrap_or_else(|| "(no lint)".to_string()),
+                By::LintGroup => d
+                    .lint
+                    .as_deref()
+                    .and_then(|l| lints.group(l))
+                    .or(d.is_error().then_some("errors"))
+                    .unwrap_or("(no group)")
+                    .to_string(),
+                By::Crate => graph.name(&d.pid).to_string(),
+            };
+            groups.entry(name).or_default().push(i);
+        }
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        groups.sort_unstable_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+        groups
+    }
+}
+
+/// where the warning overview is at
+#[derive(Default)]
+pub struct Overview {
+    pub by: By,
+    pub groups: SList,
+    pub diags: SList,
+    /// looking at the warnings of a group
+    pub drilled: bool,
+    /// of the lint explanation
+    pub scroll: u16,
+    /// showing the whole `--explain`
+    pub explain: bool,
+    /// what happened when allowing a lint
+    pub note: Option<String>,
+}
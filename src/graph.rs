@@ -0,0 +1,194 @@
+//! resolved dependency graph, from `cargo metadata`
+use anyhow::Result;
+use cargo_metadata::{MetadataCommand, Node, Package, PackageId};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+
+pub struct Graph {
+    pub meta: cargo_metadata::Metadata,
+    /// pid => index into `meta.packages`
+    index: HashMap<PackageId, usize>,
+    /// pid => index into `meta.resolve.nodes`
+    nodes: HashMap<PackageId, usize>,
+    /// pid => packages that directly depend on it
+    dependents: HashMap<PackageId, Vec<PackageId>>,
+    /// pid => workspace members that (transitively) depend on it
+    users: HashMap<PackageId, Vec<PackageId>>,
+    /// `cargo tree`, flattened
+    pub tree: Vec<Row>,
+    /// names of crates present in more than one version
+    duplicates: HashSet<String>,
+}
+
+pub struct Row {
+    pub depth: usize,
+    pub pid: PackageId,
+    /// already expanded above
+    pub repeat: bool,
+}
+
+impl Graph {
+    pub fn new(at: &Path) -> Result<Self> {
+        log::info!("resolving dependency graph");
+        let mut cmd = MetadataCommand::new();
+        cmd.current_dir(at);
+        // deps for other platforms never get built, theyd wait forever
+        match host(at) {
+            Some(host) => _ = cmd.other_options(["--filter-platform".into(), host]),
+            None => log::warn!("no host triple from `rustc -vV`, showing every platform"),
+        }
+        let meta = cmd.exec()?;
+        let index = meta
+            .packages
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.id.clone(), i))
+            .collect();
+        let mut nodes = HashMap::new();
+        let mut dependents = HashMap::<_, Vec<_>>::new();
+        for (i, node) in meta.resolve.iter().flat_map(|r| &r.nodes).enumerate() {
+            nodes.insert(node.id.clone(), i);
+            for dep in &node.dependencies {
+                dependents
+                    .entry(dep.clone())
+                    .or_default()
+                    .push(node.id.clone());
+            }
+        }
+        let mut g = Self {
+            meta,
+            index,
+            nodes,
+            dependents,
+            users: HashMap::new(),
+            tree: vec![],
+            duplicates: HashSet::new(),
+        };
+        for member in g.meta.workspace_members.clone() {
+            let mut seen = HashSet::new();
+            let mut stack = vec![&member];
+            while let Some(pid) = stack.pop() {
+                for dep in g.node(pid).into_iter().flat_map(|n| &n.dependencies) {
+                    if seen.insert(dep) {
+                        stack.push(dep);
+                    }
+                }
+            }
+            for pid in seen.into_iter().cloned().collect::<Vec<_>>() {
+                g.users.entry(pid).or_default().push(member.clone());
+            }
+        }
+        let mut seen = HashSet::new();
+        for member in g.meta.workspace_members.clone() {
+            g.flatten(&member, 0, &mut seen);
+        }
+        let mut names = HashMap::<_, usize>::new();
+        for node in g.meta.resolve.iter().flat_map(|r| &r.nodes) {
+            *names.entry(g.name(&node.id)).or_default() += 1;
+        }
+        g.duplicates = names
+            .into_iter()
+            .filter(|&(_, n)| n > 1)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        Ok(g)
+    }
+
+    fn flatten(&mut self, pid: &PackageId, depth: usize, seen: &mut HashSet<PackageId>) {
+        let repeat = !seen.insert(pid.clone());
+        self.tree.push(Row {
+            depth,
+            pid: pid.clone(),
+            repeat,
+        });
+        if repeat {
+            return;
+        }
+        let mut deps = self.node(pid).map_or(vec![], |n| n.dependencies.clone());
+        deps.sort_unstable_by(|a, b| self.name(a).cmp(self.name(b)));
+        for dep in deps {
+            self.flatten(&dep, depth + 1, seen);
+        }
+    }
+
+    pub fn package(&self, pid: &PackageId) -> Option<&Package> {
+        self.index.get(pid).map(|&i| &self.meta.packages[i])
+    }
+
+    pub fn node(&self, pid: &PackageId) -> Option<&Node> {
+        let i = *self.nodes.get(pid)?;
+        self.meta.resolve.as_ref().map(|r| &r.nodes[i])
+    }
+
+    /// display name of a package
+    pub fn name<'a>(&'a self, pid: &'a PackageId) -> &'a str {
+        match self.package(pid) {
+            Some(p) => &p.name,
+            None => pid.repr.split(' ').next().unwrap(),
+        }
+    }
+
+    /// features enabled for this package in the resolve
+    pub fn features(&self, pid: &PackageId) -> &[String] {
+        self.node(pid).map_or(&[], |n| &n.features)
+    }
+
+    /// packages that directly depend on `pid`
+    pub fn dependents(&self, pid: &PackageId) -> &[PackageId] {
+        self.dependents.get(pid).map_or(&[], Vec::as_slice)
+    }
+
+    /// workspace members that pull in `pid`, directly or not
+    pub fn users(&self, pid: &PackageId) -> &[PackageId] {
+        self.users.get(pid).map_or(&[], Vec::as_slice)
+    }
+
+    /// is another version of this crate also being built
+    pub fn is_duplicate(&self, pid: &PackageId) -> bool {
+        self.duplicates.contains(self.name(pid))
+    }
+
+    /// everything that (transitively) depends on `pid`
+    pub fn blocked_by(&self, pid: &PackageId, into: &mut HashSet<PackageId>) {
+        for dependent in self.dependents(pid) {
+            if into.insert(dependent.clone()) {
+                self.blocked_by(dependent, into);
+            }
+        }
+    }
+
+    pub fn is_member(&self, pid: &PackageId) -> bool {
+        self.meta.workspace_members.contains(pid)
+    }
+}
+
+/// where a package comes from
+pub fn source(p: &Package) -> String {
+    match &p.source {
+        None => format!(
+            "path {}",
+            p.manifest_path.parent().unwrap_or(&p.manifest_path)
+        ),
+        Some(s) if s.is_crates_io() => "registry crates.io".to_string(),
+        Some(s) => match s.repr.split_once('+') {
+            Some((kind, at)) => format!("{kind} {at}"),
+            None => s.repr.clone(),
+        },
+    }
+}
+
+/// the `host:` of `rustc -vV`, from `at` so toolchain overrides apply
+fn host(at: &Path) -> Option<String> {
+    let rustc = std::env::var_os("RUSTC").unwrap_or("rustc".into());
+    let out = Command::new(rustc)
+        .arg("-vV")
+        .current_dir(at)
+        .output()
+        .ok()?;
+    String::from_utf8(out.stdout)
+        .ok()?
+        .lines()
+        .find_map(|l| l.strip_prefix("host: "))
+        .map(str::to_string)
+}
@@ -0,0 +1,257 @@
+//! what we know about lints: their groups, what they do, and `--explain`
+use anyhow::{bail, Result};
+use crossbeam::channel::{bounded, Receiver, TryRecvError};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Default)]
+struct Listing {
+    /// lint => group
+    groups: HashMap<String, String>,
+    /// lint => description
+    descriptions: HashMap<String, String>,
+    /// lint => its default level, like `warn`
+    levels: HashMap<String, String>,
+}
+
+/// what `--explain` said about a lint
+pub enum Explained<'a> {
+    /// its still running
+    Loading,
+    /// it has nothing to say
+    Missing,
+    Text(&'a str),
+}
+
+#[derive(Default)]
+pub struct Lints {
+    listing: Listing,
+    explained: HashMap<String, Option<String>>,
+    /// `--explain`s that are still running
+    explaining: HashMap<String, Receiver<Option<String>>>,
+    clippy: bool,
+    dir: Option<PathBuf>,
+    rx: Option<Receiver<Listing>>,
+}
+
+impl Lints {
+    /// reads the lint listing in the background
+    pub fn new(dir: Option<&Path>, clippy: bool) -> Self {
+        let (tx, rx) = bounded(1);
+        let at = dir.map(Path::to_path_buf);
+        std::thread::spawn(move || {
+            let mut proc = if clippy {
+                let mut c = Command::new("clippy-driver");
+                c.arg("rustc");
+                c
+            } else {
+                Command::new("rustc")
+            };
+            if let Some(at) = &at {
+                // for the toolchain overrides
+                proc.current_dir(at);
+            }
+            match proc.args(["-W", "help"]).output() {
+                Ok(o) => _ = tx.send(listing(&String::from_utf8_lossy(&o.stdout))),
+                Err(e) => log::error!("couldnt list the lints: {e}"),
+            }
+        });
+        Self {
+            clippy,
+            dir: dir.map(Path::to_path_buf),
+            rx: Some(rx),
+            ..Self::default()
+        }
+    }
+
+    pub fn recv(&mut self) {
+        if let Some(l) = self.rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.listing = l;
+            self.rx = None;
+        }
+        let explained = &mut self.explained;
+        self.explaining.retain(|lint, rx| match rx.try_recv() {
+            Ok(e) => {
+                explained.insert(lint.clone(), e);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => {
+                explained.insert(lint.clone(), None);
+                false
+            }
+        });
+    }
+
+    pub fn group(&self, lint: &str) -> Option<&str> {
+        self.listing.groups.get(lint).map(|s| &**s)
+    }
+
+    pub fn description(&self, lint: &str) -> Option<&str> {
+        self.listing.descriptions.get(lint).map(|s| &**s)
+    }
+
+    /// where to read about it
+    pub fn link(&self, lint: &str) -> String {
+        if is_error_code(lint) {
+            format!("https://doc.rust-lang.org/error_codes/{lint}.html")
+        } else if let Some(name) = lint.strip_prefix("clippy::") {
+            format!("https://rust-lang.github.io/rust-clippy/master/index.html#{name}")
+        } else {
+            // the index has no anchors, the page of its default level does
+            let page = match self.listing.levels.get(lint).map(|l| &**l) {
+                Some(level @ ("allow" | "warn" | "deny")) => format!("{level}-by-default"),
+                _ => "index".to_string(),
+            };
+            format!(
+                "https://doc.rust-lang.org/rustc/lints/listing/{page}.html#{}",
+                lint.replace('_', "-")
+            )
+        }
+    }
+
+    /// `--explain`, which is slow, so in the background and only once per lint
+    pub fn explain(&mut self, lint: &str) -> Explained<'_> {
+        if self.explained.contains_key(lint) {
+            return match &self.explained[lint] {
+                Some(e) => Explained::Text(e),
+                None => Explained::Missing,
+            };
+        }
+        if self.explaining.contains_key(lint) {
+            return Explained::Loading;
+        }
+        let mut proc = if is_error_code(lint) {
+            let mut c = Command::new("rustc");
+            c.args(["--explain", lint]);
+            c
+        } else if let Some(name) = lint.strip_prefix("clippy::").filter(|_| self.clippy) {
+            let mut c = Command::new("cargo");
+            c.args(["clippy", "--explain", name]);
+            c
+        } else {
+            self.explained.insert(lint.to_string(), None);
+            return Explained::Missing;
+        };
+        if let Some(at) = &self.dir {
+            proc.current_dir(at);
+        }
+        let (tx, rx) = bounded(1);
+        std::thread::spawn(move || {
+            let explanation = proc
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).into_owned());
+            _ = tx.send(explanation);
+        });
+        self.explaining.insert(lint.to_string(), rx);
+        Explained::Loading
+    }
+}
+
+fn is_error_code(lint: &str) -> bool {
+    lint.len() == 5 && lint.starts_with('E') && lint[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+/// parses `rustc -W help`
+fn listing(help: &str) -> Listing {
+    let mut l = Listing::default();
+    let mut in_groups = false;
+    for line in help.lines() {
+        if line.starts_with("Lint groups") {
+            in_groups = true;
+            continue;
+        } else if line.starts_with("Lint checks") {
+            in_groups = false;
+            continue;
+        }
+        let line = line.trim();
+        if in_groups {
+            // `clippy::perf  clippy::box-collection, clippy::useless-vec`
+            let Some((group, lints)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            // everything is in these
+            if matches!(group, "name" | "----" | "warnings" | "clippy::all") {
+                continue;
+            }
+            for lint in lints.split(',') {
+                l.groups
+                    .entry(normalize(lint.trim()))
+                    .or_insert(group.to_string());
+            }
+        } else {
+            // `unused-variables  warn  detect variables which are assigned but never used`
+            let Some((lint, rest)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let Some((level, desc)) = rest.trim_start().split_once(char::is_whitespace) else {
+                continue;
+            };
+            l.levels.insert(normalize(lint), level.to_string());
+            l.descriptions
+                .insert(normalize(lint), desc.trim().to_string());
+        }
+    }
+    l
+}
+
+/// `clippy::needless-borrow` => `clippy::needless_borrow`, as the diagnostics say it
+fn normalize(lint: &str) -> String {
+    lint.replace('-', "_")
+}
+
+/// toggles `lint = "allow"` in the `[lints]` of a manifest. returns if its now allowed.
+pub fn toggle_allow(manifest: &Path, lint: &str) -> Result<bool> {
+    if is_error_code(lint) {
+        bail!("{lint} is an error, not a lint");
+    }
+    let (tool, name) = match lint.split_once("::") {
+        Some((tool, name)) => (tool, name),
+        None => ("rust", lint),
+    };
+    let mut doc = std::fs::read_to_string(manifest)?.parse::<toml_edit::DocumentMut>()?;
+    let lints = doc
+        .entry("lints")
+        .or_insert_with(|| {
+            // just `[lints.rust]`, no empty `[lints]`
+            let mut t = toml_edit::Table::new();
+            t.set_implicit(true);
+            toml_edit::Item::Table(t)
+        })
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("[lints] isnt a table"))?;
+    if lints.get("workspace").and_then(|w| w.as_bool()) == Some(true) {
+        bail!("{manifest:?} uses the workspace lints");
+    }
+    let table = lints
+        .entry(tool)
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("[lints.{tool}] isnt a table"))?;
+    let level = table.get(name).and_then(|l| {
+        // `lint = "allow"` or `lint = { level = "allow", priority = 1 }`
+        l.as_str()
+            .or_else(|| l.as_table_like()?.get("level")?.as_str())
+    });
+    let allowed = if level == Some("allow") {
+        table.remove(name);
+        // dont leave an empty `[lints.clippy]` or `[lints]` behind
+        if table.is_empty() {
+            lints.remove(tool);
+            if lints.is_empty() {
+                doc.remove("lints");
+            }
+        }
+        false
+    } else {
+        table.insert(name, toml_edit::value("allow"));
+        true
+    };
+    std::fs::write(manifest, doc.to_string())?;
+    Ok(allowed)
+}
@@ -1,6 +1,7 @@
 use comat::{cformat_args, cwriteln};
 use log::{Level, Metadata, Record};
 use std::{
+    collections::VecDeque,
     fs::File,
     io::Write,
     path::PathBuf,
@@ -8,19 +9,25 @@ use std::{
     time::Instant,
 };
 
+/// how many lines the log tab keeps
+const KEEP: usize = 5000;
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
 #[derive(Debug)]
 pub struct Logger {
     start: Instant,
-    file: Mutex<File>,
+    file: Option<Mutex<File>>,
+    lines: Mutex<VecDeque<Vec<u8>>>,
 }
 
 impl Logger {
-    pub fn init(level: Level, f: PathBuf) {
-        static LOGGER: OnceLock<Logger> = OnceLock::new();
+    pub fn init(level: Level, f: Option<PathBuf>) {
         LOGGER
             .set(Self {
                 start: Instant::now(),
-                file: Mutex::new(File::create(f).unwrap()),
+                file: f.map(|f| Mutex::new(File::create(f).unwrap())),
+                lines: Mutex::new(VecDeque::with_capacity(KEEP)),
             })
             .unwrap();
         log::set_logger(LOGGER.get().unwrap())
@@ -29,14 +36,23 @@ impl Logger {
     }
 }
 
+/// look at the last [`KEEP`] lines (ansi colored)
+pub fn lines<R>(f: impl FnOnce(&VecDeque<Vec<u8>>) -> R) -> R {
+    match LOGGER.get() {
+        Some(l) => f(&l.lines.lock().unwrap_or_else(PoisonError::into_inner)),
+        None => f(&VecDeque::new()),
+    }
+}
+
 impl log::Log for Logger {
     fn enabled(&self, _: &Metadata) -> bool {
         true
     }
 
     fn log(&self, record: &Record) {
+        let mut line = vec![];
         cwriteln!(
-            self.file.lock().unwrap_or_else(PoisonError::into_inner),
+            line,
             "[{} {:bold_blue}:{:blue}{green}@{:yellow}] {}",
             match record.level() {
                 Level::Error => cformat_args!("{bold_red}err{reset}"),
@@ -51,6 +67,18 @@ impl log::Log for Logger {
             record.args(),
         )
         .unwrap();
+        if let Some(file) = &self.file {
+            file.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .write_all(&line)
+                .unwrap();
+        }
+        let mut lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        if lines.len() == KEEP {
+            lines.pop_front();
+        }
+        line.pop(); // \n
+        lines.push_back(line);
     }
 
     fn flush(&self) {}
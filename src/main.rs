@@ -9,9 +9,16 @@ use crossterm::{
 };
 use log::Level as RLevel;
 use ratatui::prelude::*;
+mod app;
+pub mod bloat;
 pub mod cargo;
 pub mod compiler;
+pub mod graph;
+pub mod lints;
 mod logger;
+pub mod passes;
+pub mod proc;
+pub mod runner;
 mod test;
 pub mod ui;
 
@@ -24,8 +31,69 @@ struct Args {
     #[arg(short = 'l')]
     /// Log to LOG_FILE
     log_file: Option<PathBuf>,
-    #[arg(default_value = "trace", long = "level")]
-    log_level: Level,
+    #[arg(long = "level")]
+    /// How much to log [default: trace with a LOG_FILE, info without]
+    log_level: Option<Level>,
+    #[arg(long)]
+    /// Time the rustc passes of workspace members, with -Ztime-passes
+    time_passes: bool,
+    #[arg(long, num_args = 0..=1, default_missing_value = "short")]
+    /// Capture backtraces of failing tests
+    backtrace: Option<cargo::Backtrace>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "name,status,duration,delta,suite,history"
+    )]
+    /// Columns of the test table
+    columns: Vec<test::columns::Column>,
+    #[arg(long, default_value_t = 1.5)]
+    /// How many times slower than its median a test has to be to count as a regression
+    regression_ratio: f32,
+    #[arg(long, default_value_t = 0.005)]
+    /// How many seconds slower a test has to be to count as a regression
+    regression_floor: f32,
+    #[arg(long)]
+    /// Run each test in its own process, like nextest, instead of through cargo test
+    isolate: bool,
+    #[arg(long, value_name = "N", requires = "isolate")]
+    /// How many tests to run at once with --isolate [default: the number of cpus]
+    test_jobs: Option<usize>,
+    #[arg(long, value_name = "SECS")]
+    /// Kill tests that run for longer than SECS
+    timeout: Option<f32>,
+    #[arg(long, value_name = "PATTERN=SECS", value_parser = test::timeout::parse)]
+    /// Timeout for tests matching PATTERN, like `parser::*=5`; the first match wins over --timeout
+    timeout_for: Vec<(String, f32)>,
+    #[arg(long, value_name = "SECS", default_value_t = 5.)]
+    /// How long a test can run before it shows as slow
+    slow_warn: f32,
+    #[arg(long, value_name = "SECS", default_value_t = 30.)]
+    /// How long a test can run before it shows as very slow
+    slow_critical: f32,
+    #[arg(long, value_name = "N")]
+    /// Run failed tests again, up to N times [default: retries in [package.metadata.kewl], or 0]
+    retries: Option<usize>,
+    #[command(subcommand)]
+    command: Option<Cmd>,
+}
+
+#[derive(clap::Subcommand, Clone, PartialEq, Eq)]
+pub enum Cmd {
+    /// Build and run the tests (the default)
+    Test,
+    /// Run clippy, and explore its lints
+    Clippy,
+    /// Run one test over and over
+    Stress {
+        /// Its full name, like `parser::expr::unary_ops`
+        test: String,
+        #[arg(long, default_value_t = 100)]
+        runs: usize,
+        #[arg(short, long, default_value_t = 1)]
+        /// How many to run at once
+        jobs: usize,
+    },
 }
 
 #[repr(usize)]
@@ -52,21 +120,40 @@ macro_rules! ctext {
 use ctext;
 
 fn main() -> Result<()> {
+    if let Some(dir) = std::env::var_os(passes::ENV) {
+        std::process::exit(passes::wrap(dir.as_ref())?);
+    }
     let args = if std::env::args().next().unwrap().contains(".cargo/bin") {
         Args::parse_from(std::env::args().skip(1))
     } else {
         Args::parse()
     };
-    if let Some(log) = args.log_file {
-        logger::Logger::init(args.log_level.into(), log);
-    }
+    // the log tab is always there, but everything cargo says is a lot to keep
+    let level = args.log_level.unwrap_or(match args.log_file {
+        Some(_) => Level::Trace,
+        None => Level::Info,
+    });
+    logger::Logger::init(level.into(), args.log_file);
     log::info!("startup");
     let mut stdout = std::io::stdout();
-    let meta = cargo::meta(
-        args.directory
-            .as_deref()
-            .unwrap_or(&std::env::current_dir()?),
-    )?;
+    let cwd = std::env::current_dir()?;
+    let meta = cargo::meta(args.directory.as_deref().unwrap_or(&cwd))?;
+    let graph = graph::Graph::new(args.directory.as_deref().unwrap_or(&cwd))?;
+
+    let opts = cargo::Options {
+        passes: args.time_passes.then(|| {
+            graph
+                .meta
+                .target_directory
+                .join("kewl")
+                .join("passes")
+                .into()
+        }),
+        backtrace: args.backtrace,
+        isolate: args
+            .isolate
+            .then(|| args.test_jobs.unwrap_or_else(cargo::jobs)),
+    };
 
     enable_raw_mode()?;
     execute!(stdout, EnableMouseCapture, EnterAlternateScreen)?;
@@ -78,7 +165,33 @@ fn main() -> Result<()> {
         execute!(std::io::stdout(), DisableMouseCapture, LeaveAlternateScreen).unwrap();
         h(panic);
     }));
-    let res = test::run(&mut terminal, args.directory.as_deref(), &meta);
+    let res = app::run(
+        &mut terminal,
+        args.directory.as_deref(),
+        &meta,
+        &graph,
+        &opts,
+        &args.command.unwrap_or(Cmd::Test),
+        test::Options {
+            columns: args.columns,
+            threshold: test::history::Threshold {
+                ratio: args.regression_ratio,
+                floor: args.regression_floor,
+            },
+            timeouts: test::timeout::Timeouts {
+                all: args.timeout,
+                by_name: args.timeout_for,
+            },
+            retries: args
+                .retries
+                .or(meta.package.metadata.kewl.retries)
+                .unwrap_or(0),
+            slow: test::Slow {
+                warn: args.slow_warn,
+                critical: args.slow_critical,
+            },
+        },
+    );
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
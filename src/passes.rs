@@ -0,0 +1,183 @@
+//! `-Ztime-passes` for workspace members.
+//!
+//! cargo runs us as the `RUSTC_WORKSPACE_WRAPPER`, we run rustc with `-Ztime-passes`,
+//! and write its timings to `$KEWL_PASSES/<unit>`, to be picked up once the artifact shows up.
+use anyhow::Result;
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// set for the wrapper, to the directory to put the timings in
+pub const ENV: &str = "KEWL_PASSES";
+
+#[derive(Default, Clone, Copy)]
+pub struct Passes {
+    pub expand: f64,
+    pub type_check: f64,
+    pub borrow_check: f64,
+    pub mono: f64,
+    pub codegen: f64,
+    pub llvm: f64,
+    pub link: f64,
+    pub total: f64,
+}
+
+impl Passes {
+    pub fn parse(s: &str) -> Self {
+        let mut p = Self::default();
+        for line in s.lines() {
+            // time:   0.002; rss:   42MB ->   52MB (  +11MB)	expand_crate
+            let Some((time, pass)) = line
+                .strip_prefix("time:")
+                .and_then(|l| Some((l.split_once(';')?.0, l.rsplit_once('\t')?.1)))
+            else {
+                continue;
+            };
+            let Ok(time) = time.trim().parse::<f64>() else {
+                continue;
+            };
+            match pass.trim() {
+                "macro_expand_crate" => p.expand += time,
+                "type_check_crate" => p.type_check += time,
+                "MIR_borrow_checking" => p.borrow_check += time,
+                "monomorphization_collector_root_collections"
+                | "monomorphization_collector_graph_walk" => p.mono += time,
+                "codegen_to_LLVM_IR" => p.codegen += time,
+                "LLVM_passes" | "finish_ongoing_codegen" => p.llvm += time,
+                "link" => p.link += time,
+                "total" => p.total += time,
+                _ => {}
+            }
+        }
+        p
+    }
+
+    /// (name, seconds), in compilation order
+    pub fn breakdown(&self) -> [(&'static str, f64); 8] {
+        let known = self.expand
+            + self.type_check
+            + self.borrow_check
+            + self.mono
+            + self.codegen
+            + self.llvm
+            + self.link;
+        [
+            ("expansion", self.expand),
+            ("type check", self.type_check),
+            ("borrow check", self.borrow_check),
+            ("monomorphization", self.mono),
+            ("codegen", self.codegen),
+            ("llvm", self.llvm),
+            ("link", self.link),
+            ("other", (self.total - known).max(0.)),
+        ]
+    }
+}
+
+/// name of the file for a unit, computed from what cargo knows.
+/// matches up with [`wrapped_unit`].
+pub fn unit(name: &str, crate_types: &[String], test: bool) -> String {
+    if test {
+        format!("{} test", name.replace('-', "_"))
+    } else {
+        format!("{} {}", name.replace('-', "_"), crate_types.join(","))
+    }
+}
+
+/// name of the file for a unit, computed from rustcs arguments
+fn wrapped_unit(args: &[String]) -> Option<String> {
+    let mut name = None;
+    let mut types = vec![];
+    let mut test = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match &**arg {
+            "--crate-name" => name = args.next(),
+            "--crate-type" => types.extend(args.next().cloned()),
+            "--test" => test = true,
+            _ => {}
+        }
+    }
+    // cargo probes rustc with `--crate-name ___`
+    let name = name.filter(|&n| n != "___")?;
+    Some(unit(name, &types, test))
+}
+
+pub fn load(dir: &Path, unit: &str) -> Option<Passes> {
+    Some(Passes::parse(
+        &std::fs::read_to_string(dir.join(unit)).ok()?,
+    ))
+}
+
+/// we are the wrapper: `kewl rustc args..`
+pub fn wrap(dir: &Path) -> Result<i32> {
+    let mut args = std::env::args().skip(1);
+    let rustc = args.next().unwrap();
+    let args = args.collect::<Vec<_>>();
+    let Some(unit) = wrapped_unit(&args) else {
+        // `rustc -vV` and friends
+        return Ok(Command::new(rustc).args(args).status()?.code().unwrap_or(1));
+    };
+    let mut proc = Command::new(rustc)
+        .args(&args)
+        .arg("-Ztime-passes")
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut passes = String::new();
+    let mut stderr = std::io::stderr().lock();
+    for line in BufReader::new(proc.stderr.take().unwrap()).lines() {
+        let line = line?;
+        if line.starts_with("time:") {
+            passes.push_str(&line);
+            passes.push('\n');
+        } else {
+            // cargo wants the diagnostics
+            writeln!(stderr, "{line}")?;
+        }
+    }
+    let status = proc.wait()?;
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(unit), passes)?;
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_passes() {
+        let p = Passes::parse(
+            "time:   0.003; rss:   41MB ->   50MB (   +8MB)\texpand_crate
+time:   0.003; rss:   41MB ->   50MB (   +8MB)\tmacro_expand_crate
+time:   0.001; rss:   53MB ->   59MB (   +6MB)\ttype_check_crate
+time:   0.001; rss:   59MB ->   64MB (   +5MB)\tMIR_borrow_checking
+time:   0.000; rss:   64MB ->   65MB (   +1MB)\tmonomorphization_collector_root_collections
+time:   0.003; rss:   65MB ->   75MB (  +10MB)\tmonomorphization_collector_graph_walk
+time:   0.001; rss:   78MB ->   84MB (   +6MB)\tcodegen_to_LLVM_IR
+time:   0.005; rss:   81MB ->   73MB (   -7MB)\tLLVM_passes
+time:   0.004; rss:   69MB ->   74MB (   +5MB)\tfinish_ongoing_codegen
+time:   0.047; rss:   74MB ->   75MB (   +1MB)\tlink
+warning: unused variable: `x`
+time:   0.070; rss:   27MB ->   74MB (  +47MB)\ttotal
+",
+        );
+        assert_eq!(p.expand, 0.003);
+        assert_eq!(p.type_check, 0.001);
+        assert_eq!(p.borrow_check, 0.001);
+        assert_eq!(p.mono, 0.003);
+        assert_eq!(p.codegen, 0.001);
+        // both add up to the llvm time
+        assert!((p.llvm - 0.009).abs() < 1e-9);
+        assert_eq!(p.link, 0.047);
+        assert_eq!(p.total, 0.07);
+    }
+
+    #[test]
+    fn not_time_passes() {
+        let p = Passes::parse("time: soon; rss: 1MB\ttotal\ntime:   0.5 total\n");
+        assert_eq!(p.total, 0.);
+    }
+}
@@ -0,0 +1,162 @@
+//! sampling from `/proc`
+use std::{collections::HashMap, fs::read_to_string, sync::OnceLock};
+
+/// direct children of `pid`
+pub fn children(pid: u32) -> Vec<u32> {
+    let mut kids = vec![];
+    let Ok(tasks) = std::fs::read_dir(format!("/proc/{pid}/task")) else {
+        return kids;
+    };
+    for task in tasks.flatten() {
+        if let Ok(c) = read_to_string(task.path().join("children")) {
+            kids.extend(c.split_whitespace().filter_map(|p| p.parse::<u32>().ok()));
+        }
+    }
+    kids
+}
+
+/// everything below `pid`
+pub fn descendants(pid: u32) -> Vec<u32> {
+    // kernels without CONFIG_PROC_CHILDREN dont have the children file,
+    // so there we have to check everyones parent
+    let tree =
+        (!std::path::Path::new(&format!("/proc/{pid}/task/{pid}/children")).exists()).then(parents);
+    let mut all = vec![];
+    let mut stack = vec![pid];
+    while let Some(p) = stack.pop() {
+        let kids = match &tree {
+            Some(tree) => tree.get(&p).cloned().unwrap_or_default(),
+            None => children(p),
+        };
+        for kid in kids {
+            all.push(kid);
+            stack.push(kid);
+        }
+    }
+    all
+}
+
+/// ppid => pids
+fn parents() -> HashMap<u32, Vec<u32>> {
+    let mut tree = HashMap::<_, Vec<_>>::new();
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return tree;
+    };
+    for p in procs
+        .flatten()
+        .filter_map(|p| p.file_name().to_str()?.parse::<u32>().ok())
+    {
+        if let Some(s) = stat(p) {
+            tree.entry(s.ppid).or_default().push(p);
+        }
+    }
+    tree
+}
+
+pub struct Stat {
+    pub comm: String,
+    pub ppid: u32,
+    /// user + system time, in clock ticks
+    pub ticks: u64,
+    /// resident set size, in pages
+    pub rss: u64,
+}
+
+pub fn stat(pid: u32) -> Option<Stat> {
+    let s = read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // comm can contain spaces and parens, so split on the last paren
+    let (comm, rest) = s.split_once(" (")?.1.rsplit_once(") ")?;
+    let fields = rest.split_whitespace().collect::<Vec<_>>();
+    let field = |n: usize| fields.get(n)?.parse::<u64>().ok();
+    Some(Stat {
+        comm: comm.to_string(),
+        ppid: field(1)? as u32,
+        ticks: field(11)? + field(12)?,
+        rss: field(21)?,
+    })
+}
+
+/// system wide cpu utilization
+#[derive(Default)]
+pub struct Cpu {
+    /// (busy, total) jiffies at the last sample
+    last: Option<(u64, u64)>,
+}
+
+impl Cpu {
+    /// fraction of the time the cpus were busy since the last sample
+    pub fn sample(&mut self) -> Option<f64> {
+        let stat = read_to_string("/proc/stat").ok()?;
+        let line = stat.lines().next()?.strip_prefix("cpu")?;
+        let n = line
+            .split_whitespace()
+            .filter_map(|n| n.parse::<u64>().ok())
+            .collect::<Vec<_>>();
+        let total = n.iter().sum::<u64>();
+        // idle + iowait
+        let busy = total - n.get(3)? - n.get(4).unwrap_or(&0);
+        let (lbusy, ltotal) = self.last.replace((busy, total)).unwrap_or((busy, total));
+        if total == ltotal {
+            return None;
+        }
+        Some((busy - lbusy) as f64 / (total - ltotal) as f64)
+    }
+}
+
+/// clock ticks a second, asked for once
+pub fn ticks() -> f64 {
+    static TICKS: OnceLock<f64> = OnceLock::new();
+    *TICKS.get_or_init(|| {
+        // SAFETY: no pointers involved
+        match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+            n if n > 0 => n as f64,
+            _ => 100.,
+        }
+    })
+}
+
+/// a `kB` field of `/proc/<pid>/status`
+fn status(pid: u32, field: &str) -> Option<u64> {
+    read_to_string(format!("/proc/{pid}/status"))
+        .ok()?
+        .lines()
+        .find_map(|l| l.strip_prefix(field)?.strip_prefix(':'))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// the memory `pid` has resident, in kilobytes
+pub fn rss(pid: u32) -> Option<u64> {
+    status(pid, "VmRSS")
+}
+
+/// the most memory `pid` has had resident, in kilobytes
+pub fn peak_rss(pid: u32) -> Option<u64> {
+    status(pid, "VmHWM")
+}
+
+/// what `pid` is running
+pub fn exe(pid: u32) -> Option<std::path::PathBuf> {
+    std::fs::read_link(format!("/proc/{pid}/exe")).ok()
+}
+
+/// the arguments `pid` was started with, with its name first
+pub fn cmdline(pid: u32) -> Vec<String> {
+    read_to_string(format!("/proc/{pid}/cmdline"))
+        .map(|c| c.split_terminator('\0').map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// where `pid` is running
+pub fn cwd(pid: u32) -> Option<std::path::PathBuf> {
+    std::fs::read_link(format!("/proc/{pid}/cwd")).ok()
+}
+
+/// with `SIGKILL`, theres no asking a hung test nicely
+pub fn kill(pid: u32) -> bool {
+    // SAFETY: no pointers involved
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) == 0 }
+}
@@ -0,0 +1,581 @@
+//! running test binaries ourselves, without going through `cargo test`
+use anyhow::Result;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::{diagnostic::DiagnosticLevel, Message};
+use crossbeam::channel::{bounded, Receiver, Sender};
+use std::{
+    io::{BufRead, BufReader, Read},
+    os::unix::process::ExitStatusExt,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::cargo::{Backtrace, Options, RawTestMessage, TestEvent, TestMessage};
+use crate::test::{stress::Sample, timeout::Timeouts};
+
+#[derive(Clone)]
+pub struct TestBinary {
+    pub path: Utf8PathBuf,
+    /// of the package, where cargo would run it
+    pub dir: Utf8PathBuf,
+    /// the root of the target, like `src/main.rs`
+    pub src: Utf8PathBuf,
+}
+
+impl TestBinary {
+    /// the tests in it
+    pub fn list(&self) -> Result<Vec<String>> {
+        let out = Command::new(&self.path)
+            .current_dir(&self.dir)
+            .args(["--list", "--format", "terse"])
+            .output()?;
+        Ok(String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(|l| l.strip_suffix(": test"))
+            .map(String::from)
+            .collect())
+    }
+
+    /// what cargo calls it when it runs it, so the history matches
+    pub fn suite(&self, cwd: &Path) -> String {
+        let src = self.src.as_std_path();
+        src.strip_prefix(cwd).unwrap_or(src).display().to_string()
+    }
+
+    fn command(&self, backtrace: Option<Backtrace>) -> Command {
+        let mut proc = Command::new(&self.path);
+        if let Some(b) = backtrace {
+            b.set(&mut proc);
+        }
+        proc.current_dir(&self.dir)
+            .env("CARGO_MANIFEST_DIR", &self.dir)
+            .args([
+                "-Zunstable-options",
+                "--report-time",
+                "--show-output",
+                "--format",
+                "json",
+            ]);
+        proc
+    }
+}
+
+/// what a package builds its tests from
+const SOURCES: [&str; 6] = [
+    "src",
+    "tests",
+    "benches",
+    "examples",
+    "build.rs",
+    "Cargo.toml",
+];
+
+/// if a source file of the packages is newer than their binaries
+fn stale(binaries: &[TestBinary]) -> bool {
+    let mtime = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+    let Some(built) = binaries
+        .iter()
+        .map(|b| mtime(b.path.as_std_path()).unwrap_or(SystemTime::UNIX_EPOCH))
+        .min()
+    else {
+        return false;
+    };
+    fn newest(p: &Path, mtime: &dyn Fn(&Path) -> Option<SystemTime>) -> Option<SystemTime> {
+        if !p.is_dir() {
+            return mtime(p);
+        }
+        std::fs::read_dir(p)
+            .ok()?
+            .flatten()
+            .filter_map(|e| newest(&e.path(), mtime))
+            .max()
+    }
+    binaries.iter().any(|b| {
+        SOURCES
+            .iter()
+            .filter_map(|p| newest(b.dir.join(p).as_std_path(), &mtime))
+            .any(|t| t > built)
+    })
+}
+
+/// `cargo test --no-run`, for fresh binaries
+fn rebuild(at: Option<&Path>, binaries: &[TestBinary]) -> Result<Vec<TestBinary>> {
+    let out = crate::cargo::cargo(at)
+        .args(["test", "--no-run", "--message-format", "json"])
+        .stdin(Stdio::null())
+        .output()?;
+    for line in String::from_utf8_lossy(&out.stderr).lines() {
+        log::info!("cargo: {line}");
+    }
+    let mut fresh = vec![];
+    for m in Message::parse_stream(&*out.stdout).flatten() {
+        match m {
+            Message::CompilerArtifact(a) => {
+                if let Some(path) = a.executable.filter(|_| a.profile.test) {
+                    let dir = a.manifest_path.parent().unwrap_or(Utf8Path::new("")).into();
+                    fresh.push(TestBinary {
+                        path,
+                        dir,
+                        src: a.target.src_path,
+                    });
+                }
+            }
+            // the build tab is done with, so the log is where they show
+            Message::CompilerMessage(m) if m.message.level == DiagnosticLevel::Error => {
+                log::error!(
+                    "{}",
+                    m.message
+                        .rendered
+                        .as_deref()
+                        .unwrap_or(&m.message.message)
+                        .trim_end()
+                );
+            }
+            _ => {}
+        }
+    }
+    if !out.status.success() {
+        anyhow::bail!("the rebuild failed, its errors are in the log");
+    }
+    // theres always a binary, or there wouldnt be a test to rerun
+    Ok(if fresh.is_empty() {
+        binaries.to_vec()
+    } else {
+        fresh
+    })
+}
+
+/// the binaries, rebuilt if the sources changed since
+fn current(at: Option<&Path>, binaries: Vec<TestBinary>) -> Result<Vec<TestBinary>> {
+    if !stale(&binaries) {
+        return Ok(binaries);
+    }
+    log::info!("sources changed, rebuilding");
+    rebuild(at, &binaries)
+}
+
+/// runs `names` with `--exact`, in only the binaries that have them.
+/// goes through cargo first if the binaries are out of date.
+pub fn rerun(
+    at: Option<&Path>,
+    binaries: &[TestBinary],
+    names: Vec<String>,
+    opts: &Options,
+) -> Receiver<TestMessage> {
+    let (tx, rx) = bounded(10);
+    let backtrace = opts.backtrace;
+    let cwd = cwd(at);
+    let at = at.map(Path::to_path_buf);
+    let binaries = binaries.to_vec();
+    std::thread::spawn(move || {
+        let binaries = match current(at.as_deref(), binaries) {
+            Ok(b) => b,
+            Err(e) => {
+                log::error!("couldnt rebuild: {e}");
+                _ = tx.send(TestMessage::Finished);
+                return;
+            }
+        };
+        for b in &binaries {
+            let has = match b.list() {
+                Ok(tests) => names
+                    .iter()
+                    .filter(|n| tests.contains(n))
+                    .collect::<Vec<_>>(),
+                Err(e) => {
+                    log::error!("couldnt list the tests of {}: {e}", b.path);
+                    continue;
+                }
+            };
+            if has.is_empty() {
+                continue;
+            }
+            if tx.send(TestMessage::Running(b.suite(&cwd))).is_err() {
+                return;
+            }
+            let mut proc = b.command(backtrace);
+            proc.arg("--exact").args(has);
+            if let Err(e) = run(proc, &tx) {
+                log::error!("couldnt run {}: {e}", b.path);
+            }
+        }
+        _ = tx.send(TestMessage::Finished);
+    });
+    rx
+}
+
+/// runs `name` `runs` times, `jobs` at a time, each in its own process,
+/// killing the ones that run past `timeout` seconds
+pub fn stress(
+    at: Option<&Path>,
+    binaries: &[TestBinary],
+    name: String,
+    runs: usize,
+    jobs: usize,
+    opts: &Options,
+    timeout: Option<f32>,
+) -> Receiver<Sample> {
+    let (tx, rx) = bounded(jobs * 2);
+    let backtrace = opts.backtrace;
+    let at = at.map(Path::to_path_buf);
+    let binaries = binaries.to_vec();
+    std::thread::spawn(move || {
+        let binaries = match current(at.as_deref(), binaries) {
+            Ok(b) => b,
+            Err(e) => return log::error!("couldnt rebuild: {e}"),
+        };
+        let Some(b) = binaries
+            .into_iter()
+            .find(|b| b.list().is_ok_and(|t| t.contains(&name)))
+        else {
+            return log::error!("no test binary has {name}");
+        };
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..jobs.max(1) {
+                s.spawn(|| {
+                    while next.fetch_add(1, Ordering::Relaxed) < runs {
+                        let mut proc = b.command(backtrace);
+                        proc.args(["--exact", "--include-ignored", &name]);
+                        let sample = match once(proc, timeout) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                log::error!("couldnt run {}: {e}", b.path);
+                                return;
+                            }
+                        };
+                        // nobody is watching anymore
+                        if tx.send(sample).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+    });
+    rx
+}
+
+/// how a test that ran alone ended
+#[derive(Debug, Default)]
+pub struct Exit {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    /// in kilobytes
+    pub peak_rss: Option<u64>,
+    pub stderr: String,
+    /// we killed it for running past its timeout
+    pub timed_out: bool,
+}
+
+impl Exit {
+    /// like `killed by SIGSEGV`
+    pub fn why(&self) -> String {
+        if self.timed_out {
+            return "killed after its timeout".to_string();
+        }
+        match (self.code, self.signal) {
+            (_, Some(s)) => format!("killed by {}", signal(s)),
+            (Some(c), _) => format!("exited with {c}"),
+            _ => "exited".to_string(),
+        }
+    }
+}
+
+fn signal(n: i32) -> String {
+    match n {
+        4 => "SIGILL",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        15 => "SIGTERM",
+        n => return format!("signal {n}"),
+    }
+    .to_string()
+}
+
+/// what cargo shows paths relative to
+pub fn cwd(at: Option<&Path>) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    at.map_or_else(|| cwd.clone(), |at| cwd.join(at))
+}
+
+/// runs every test, or just `only`, in its own process, `jobs` at a time, like nextest
+pub fn isolated(
+    at: Option<&Path>,
+    binaries: &[TestBinary],
+    only: Option<Vec<String>>,
+    jobs: usize,
+    opts: &Options,
+    timeouts: &Timeouts,
+) -> Receiver<TestMessage> {
+    let (tx, rx) = bounded(jobs * 4);
+    let backtrace = opts.backtrace;
+    let cwd = cwd(at);
+    let at = at.map(Path::to_path_buf);
+    let binaries = binaries.to_vec();
+    let timeouts = timeouts.clone();
+    std::thread::spawn(move || {
+        let binaries = match current(at.as_deref(), binaries) {
+            Ok(b) => b,
+            Err(e) => {
+                log::error!("couldnt rebuild: {e}");
+                _ = tx.send(TestMessage::Finished);
+                return;
+            }
+        };
+        let mut queue = vec![];
+        for b in &binaries {
+            match b.list() {
+                Ok(tests) => queue.extend(
+                    tests
+                        .into_iter()
+                        .filter(|t| only.as_ref().is_none_or(|o| o.contains(t)))
+                        .map(|t| (b, b.suite(&cwd), t)),
+                ),
+                Err(e) => log::error!("couldnt list the tests of {}: {e}", b.path),
+            }
+        }
+        if tx.send(TestMessage::Pool(queue.len())).is_err() {
+            return;
+        }
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..jobs.max(1) {
+                s.spawn(|| {
+                    while let Some((b, suite, name)) =
+                        queue.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let mut proc = b.command(backtrace);
+                        proc.args(["--exact", name]);
+                        if let Err(e) = alone(proc, suite, name, timeouts.of(name), &tx) {
+                            log::error!("couldnt run {name}: {e}");
+                        }
+                    }
+                });
+            }
+        });
+        _ = tx.send(TestMessage::Finished);
+    });
+    rx
+}
+
+/// runs one test in its own process, watching its memory and time
+fn alone(
+    mut proc: Command,
+    suite: &str,
+    name: &str,
+    timeout: Option<f32>,
+    tx: &Sender<TestMessage>,
+) -> Result<()> {
+    log::trace!("running {proc:?}");
+    let started = Instant::now();
+    let mut child = proc
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let (out, mut err) = (child.stdout.take().unwrap(), child.stderr.take().unwrap());
+    let send = |event| {
+        tx.send(TestMessage::Isolated {
+            suite: suite.to_string(),
+            event,
+        })
+        .map_err(anyhow::Error::from)
+    };
+    let mut exit = Exit::default();
+    let (began, ended) = std::thread::scope(|s| -> Result<_> {
+        let out = s.spawn(|| {
+            // if libtest said it started, and how it went
+            let (mut began, mut ended) = (false, false);
+            for line in BufReader::new(out).lines().map_while(Result::ok) {
+                match serde_json::from_str::<RawTestMessage>(&line) {
+                    Ok(m) => {
+                        match &m {
+                            RawTestMessage::Test(TestEvent::Started { .. }) => began = true,
+                            // libtests own timeout is only a warning
+                            RawTestMessage::Test(TestEvent::Timeout { .. }) => {}
+                            RawTestMessage::Test(_) => ended = true,
+                            _ => {}
+                        }
+                        if send(m).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => log::debug!("test: {line}"),
+                }
+            }
+            (began, ended)
+        });
+        let stderr = s.spawn(move || {
+            let mut s = String::new();
+            _ = err.read_to_string(&mut s);
+            s
+        });
+        wait(&mut child, started, timeout, &mut exit)?;
+        if exit.timed_out {
+            log::warn!("{name} timed out, killed it");
+        }
+        exit.stderr = stderr.join().unwrap_or_default();
+        Ok(out.join().unwrap_or_default())
+    })?;
+    let name = name.to_string();
+    if !began {
+        send(RawTestMessage::Test(TestEvent::Started {
+            name: name.clone(),
+        }))?;
+    }
+    // it died before libtest could say how it went. if we killed it, the exit says so
+    if !ended && !exit.timed_out {
+        send(RawTestMessage::Test(TestEvent::Failed {
+            name: name.clone(),
+            exec_time: started.elapsed().as_secs_f32(),
+            stdout: Some(exit.why()),
+            reason: None,
+            message: None,
+        }))?;
+    }
+    tx.send(TestMessage::Exited {
+        suite: suite.to_string(),
+        name,
+        exit: Box::new(exit),
+    })?;
+    Ok(())
+}
+
+/// waits for `child` to exit, killing it past `timeout` seconds, and says how into `exit`
+fn wait(child: &mut Child, started: Instant, timeout: Option<f32>, exit: &mut Exit) -> Result<()> {
+    let pid = child.id() as libc::pid_t;
+    let us = std::env::current_exe().ok();
+    let status = loop {
+        // reaping it ourselves gets its peak rss, which is gone once std reaps it
+        let mut status = 0;
+        // SAFETY: its all integers
+        let mut usage = unsafe { std::mem::zeroed::<libc::rusage>() };
+        // SAFETY: both point to live locals
+        match unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut usage) } {
+            0 => {}
+            -1 if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted => {}
+            -1 => return Err(std::io::Error::last_os_error().into()),
+            _ => {
+                // in kilobytes. it counts what the process had before its exec, which is us,
+                // so its only the tests own when its more than ours
+                let max = usage.ru_maxrss as u64;
+                if crate::proc::peak_rss(std::process::id()).is_some_and(|ours| max > ours) {
+                    exit.peak_rss = Some(max);
+                }
+                break std::process::ExitStatus::from_raw(status);
+            }
+        }
+        // before the exec its still us, and less than a mebibyte is from the middle of it
+        if crate::proc::exe(pid as u32) != us {
+            if let Some(kb) = crate::proc::peak_rss(pid as u32).filter(|&kb| kb >= 1024) {
+                exit.peak_rss = Some(kb);
+            }
+        }
+        if !exit.timed_out && timeout.is_some_and(|t| started.elapsed().as_secs_f32() > t) {
+            child.kill()?;
+            exit.timed_out = true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    exit.code = status.code();
+    exit.signal = status.signal();
+    Ok(())
+}
+
+/// kills whatever under `root`, or `root` itself, is the binary running `test`.
+/// returns that binary and all its tests, to run the rest again.
+pub fn kill(root: u32, binaries: &[TestBinary], test: &str) -> Option<(TestBinary, Vec<String>)> {
+    for pid in std::iter::once(root).chain(crate::proc::descendants(root)) {
+        let Some(exe) = crate::proc::exe(pid) else {
+            continue;
+        };
+        let Some(b) = binaries.iter().find(|b| b.path.as_std_path() == exe) else {
+            continue;
+        };
+        let Ok(tests) = b.list() else { continue };
+        if !tests.iter().any(|t| t == test) {
+            continue;
+        }
+        log::info!("killing {} ({pid})", b.path);
+        if !crate::proc::kill(pid) {
+            log::error!("couldnt kill {pid}");
+            return None;
+        }
+        return Some((b.clone(), tests));
+    }
+    None
+}
+
+/// the outcome of a binary running one test, killed after `timeout` seconds
+fn once(mut proc: Command, timeout: Option<f32>) -> Result<Sample> {
+    let started = Instant::now();
+    let mut child = proc
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let out = child.stdout.take().unwrap();
+    let out = std::thread::spawn(move || {
+        let mut s = String::new();
+        _ = BufReader::new(out).read_to_string(&mut s);
+        s
+    });
+    let mut exit = Exit::default();
+    wait(&mut child, started, timeout, &mut exit)?;
+    for line in out.join().unwrap_or_default().lines() {
+        match serde_json::from_str::<RawTestMessage>(line) {
+            Ok(RawTestMessage::Test(TestEvent::Ok {
+                exec_time, stdout, ..
+            })) => {
+                return Ok(Sample {
+                    passed: true,
+                    time: exec_time,
+                    stdout,
+                })
+            }
+            Ok(RawTestMessage::Test(TestEvent::Failed {
+                exec_time, stdout, ..
+            })) => {
+                return Ok(Sample {
+                    passed: false,
+                    time: exec_time,
+                    stdout,
+                })
+            }
+            _ => {}
+        }
+    }
+    // it crashed or hung before libtest could say how it went
+    Ok(Sample {
+        passed: false,
+        time: started.elapsed().as_secs_f32(),
+        stdout: Some(exit.why()),
+    })
+}
+
+/// forwards the libtest json of a test binary
+fn run(mut proc: Command, tx: &Sender<TestMessage>) -> Result<()> {
+    log::trace!("running {proc:?}");
+    let mut proc = proc
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    tx.send(TestMessage::Spawned(proc.id()))?;
+    for line in BufReader::new(proc.stdout.take().unwrap()).lines() {
+        let line = line?;
+        match serde_json::from_str::<RawTestMessage>(&line) {
+            Ok(m) => tx.send(TestMessage::Event(m))?,
+            // stray prints
+            Err(_) => log::debug!("test: {line}"),
+        }
+    }
+    proc.wait()?;
+    Ok(())
+}
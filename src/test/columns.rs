@@ -0,0 +1,120 @@
+//! the columns of the test table, and sorting by them
+use std::cmp::Ordering;
+
+use super::TestState;
+use crate::cargo::TestEvent;
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Column {
+    Name,
+    Status,
+    Duration,
+    /// against the median of earlier runs
+    Delta,
+    /// the binary it ran in
+    Suite,
+    /// how it went the last runs
+    History,
+    /// peak rss, of tests that ran in their own process
+    Memory,
+}
+
+impl Column {
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Status => "status",
+            Column::Duration => "duration",
+            Column::Delta => "delta",
+            Column::Suite => "suite",
+            Column::History => "history",
+            Column::Memory => "memory",
+        }
+    }
+}
+
+/// failures first, then what is still going
+fn rank(t: &TestEvent) -> u8 {
+    match t {
+        TestEvent::Failed { .. } => 0,
+        TestEvent::Timeout { .. } => 1,
+        TestEvent::Started { .. } => 2,
+        TestEvent::Ok { .. } => 3,
+        TestEvent::Ignored { .. } => 4,
+    }
+}
+
+pub fn duration(t: &TestEvent) -> f32 {
+    match t {
+        TestEvent::Ok { exec_time, .. } | TestEvent::Failed { exec_time, .. } => *exec_time,
+        _ => 0.,
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct Sort {
+    /// in the order tests started, if none
+    pub by: Option<Column>,
+    pub reverse: bool,
+}
+
+impl Sort {
+    /// to the next of `columns`, and then back to none
+    pub fn next(&mut self, columns: &[Column]) {
+        self.by = match self.by.and_then(|c| columns.iter().position(|&x| x == c)) {
+            None => columns.first().copied(),
+            Some(i) => columns.get(i + 1).copied(),
+        };
+        // slowest and biggest first
+        self.reverse = matches!(
+            self.by,
+            Some(Column::Duration | Column::Delta | Column::Memory)
+        );
+    }
+
+    /// what the test at `i` sorts by, worked out once a sort
+    pub fn key<'a>(&self, state: &'a TestState, i: usize) -> Key<'a> {
+        let t = &state.tests[i];
+        match self.by {
+            None => Key::None,
+            Some(Column::Name) => Key::Name(t.name()),
+            Some(Column::Status) => Key::Int(rank(t).into()),
+            Some(Column::Duration) => Key::Secs(duration(t)),
+            Some(Column::Delta) => Key::Secs(state.delta(i).map_or(0., |(m, n)| n / m)),
+            Some(Column::Suite) => Key::Int(state.suite_of[i] as u64),
+            // flaky ones, then by failures
+            Some(Column::History) => {
+                let (recent, flaky) = state.history(i);
+                Key::History(!flaky, usize::MAX - recent.iter().filter(|&&p| !p).count())
+            }
+            Some(Column::Memory) => Key::Memory(state.exits.get(&i).and_then(|e| e.peak_rss)),
+        }
+    }
+
+    /// of two keys from [`Sort::key`]
+    pub fn cmp(&self, a: &Key, b: &Key) -> Ordering {
+        let o = match (a, b) {
+            (Key::Name(a), Key::Name(b)) => a.cmp(b),
+            (Key::Int(a), Key::Int(b)) => a.cmp(b),
+            (Key::Secs(a), Key::Secs(b)) => a.total_cmp(b),
+            (Key::History(a, x), Key::History(b, y)) => (a, x).cmp(&(b, y)),
+            (Key::Memory(a), Key::Memory(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        };
+        if self.reverse {
+            o.reverse()
+        } else {
+            o
+        }
+    }
+}
+
+/// see [`Sort::key`]
+pub enum Key<'a> {
+    None,
+    Name(&'a str),
+    Int(u64),
+    Secs(f32),
+    History(bool, usize),
+    Memory(Option<u64>),
+}
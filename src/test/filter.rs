@@ -0,0 +1,108 @@
+//! narrowing down the tests
+use crate::cargo::TestEvent;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Only {
+    #[default]
+    All,
+    Failed,
+    Running,
+    Ignored,
+    Slow,
+}
+
+impl Only {
+    pub fn name(self) -> &'static str {
+        match self {
+            Only::All => "all",
+            Only::Failed => "failed",
+            Only::Running => "running",
+            Only::Ignored => "ignored",
+            Only::Slow => "slow",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Filter {
+    pub query: String,
+    /// keys go to the query
+    pub typing: bool,
+    pub only: Only,
+}
+
+impl Filter {
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty() || self.only != Only::All
+    }
+
+    /// `slow` is the seconds before a test counts as slow, `elapsed` how long it has been running
+    pub fn matches(&self, t: &TestEvent, slow: f32, elapsed: Option<f32>) -> bool {
+        let status = match self.only {
+            Only::All => true,
+            Only::Failed => matches!(t, TestEvent::Failed { .. } | TestEvent::Timeout { .. }),
+            Only::Running => matches!(t, TestEvent::Started { .. }),
+            Only::Ignored => matches!(t, TestEvent::Ignored { .. }),
+            Only::Slow => match t {
+                TestEvent::Ok { exec_time, .. } | TestEvent::Failed { exec_time, .. } => {
+                    *exec_time >= slow
+                }
+                TestEvent::Started { .. } => elapsed.is_some_and(|e| e >= slow),
+                TestEvent::Timeout { .. } => true,
+                TestEvent::Ignored { .. } => false,
+            },
+        };
+        status && fuzzy(&self.query, t.name()).is_some()
+    }
+
+    /// what chars of the name to highlight
+    pub fn matched(&self, name: &str) -> Vec<usize> {
+        fuzzy(&self.query, name).unwrap_or_default()
+    }
+}
+
+/// char indices of `name` that match `query`, in order, ignoring case.
+/// prefers matches that start words, so `pe` hits `parser::expr` at `p` and `e`.
+pub fn fuzzy(query: &str, name: &str) -> Option<Vec<usize>> {
+    let name = name.chars().collect::<Vec<_>>();
+    subsequence(query, &name, true).or_else(|| subsequence(query, &name, false))
+}
+
+fn subsequence(query: &str, name: &[char], words: bool) -> Option<Vec<usize>> {
+    let mut at = vec![];
+    let mut from = 0;
+    for q in query.chars() {
+        let eq = |c: char| c.to_lowercase().eq(q.to_lowercase());
+        let start = |i: usize| i == 0 || !name[i - 1].is_alphanumeric();
+        let i = (from..name.len())
+            .find(|&i| eq(name[i]) && start(i))
+            .or_else(|| (from..name.len()).find(|&i| eq(name[i])).filter(|_| !words))?;
+        at.push(i);
+        from = i + 1;
+    }
+    Some(at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_starts() {
+        assert_eq!(fuzzy("pe", "parser::expr"), Some(vec![0, 8]));
+        assert_eq!(
+            fuzzy("PEU", "parser::expr::unary_ops"),
+            Some(vec![0, 8, 14])
+        );
+        assert_eq!(fuzzy("uo", "parser::expr::unary_ops"), Some(vec![14, 20]));
+    }
+
+    #[test]
+    fn anywhere() {
+        // `x` starts no word, so all of it is matched in order instead
+        assert_eq!(fuzzy("px", "parser::expr"), Some(vec![0, 9]));
+        assert_eq!(fuzzy("", "parser"), Some(vec![]));
+        assert_eq!(fuzzy("ep", "parser"), None);
+        assert_eq!(fuzzy("parserr", "parser"), None);
+    }
+}
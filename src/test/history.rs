@@ -0,0 +1,239 @@
+//! outcomes of past runs, kept in `target/kewl/history.jsonl`, one run a line
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// runs to keep around
+const KEEP: usize = 200;
+/// runs to show
+pub const RECENT: usize = 10;
+/// earlier passes a test needs before its compared against them
+const MIN_RUNS: usize = 3;
+
+/// when a test counts as slower than it used to be
+#[derive(Clone, Copy)]
+pub struct Threshold {
+    /// of the median
+    pub ratio: f32,
+    /// seconds it needs to be slower by, so tiny tests dont flap
+    pub floor: f32,
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Self {
+            ratio: 1.5,
+            floor: 0.005,
+        }
+    }
+}
+
+impl Threshold {
+    pub fn regressed(self, median: f32, now: f32) -> bool {
+        now > median * self.ratio && now - median > self.floor
+    }
+
+    /// the other way around
+    pub fn improved(self, median: f32, now: f32) -> bool {
+        now * self.ratio < median && median - now > self.floor
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Run {
+    /// see [`commit`]
+    pub commit: String,
+    /// unix seconds
+    pub at: u64,
+    pub tests: Vec<Outcome>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Outcome {
+    pub suite: String,
+    pub name: String,
+    pub passed: bool,
+    /// seconds
+    pub time: f32,
+}
+
+#[derive(Default)]
+pub struct History {
+    path: PathBuf,
+    /// of this run
+    commit: String,
+    runs: Vec<Run>,
+    /// runs from before this session, what durations are compared against
+    earlier: usize,
+    /// the outcomes of a test, oldest first, as (run, index into its tests)
+    by_test: HashMap<(String, String), Vec<(usize, usize)>>,
+    /// [`History::median`] of each test, worked out when the earlier runs change
+    medians: HashMap<(String, String), f32>,
+}
+
+impl History {
+    /// loads what is there, a missing or broken store is an empty one
+    pub fn load(target: &Path, root: &Path) -> Self {
+        let path = target.join("kewl").join("history.jsonl");
+        let runs = std::fs::read_to_string(&path)
+            .map(|s| {
+                s.lines()
+                    .filter_map(|l| serde_json::from_str(l).ok())
+                    .collect::<Vec<Run>>()
+            })
+            .unwrap_or_default();
+        let mut h = Self {
+            path,
+            commit: commit(root),
+            runs: vec![],
+            earlier: runs.len(),
+            by_test: HashMap::new(),
+            medians: HashMap::new(),
+        };
+        for run in runs {
+            h.push(run);
+        }
+        h.medians();
+        h
+    }
+
+    /// works out every [`History::median`]
+    fn medians(&mut self) {
+        self.medians = self
+            .by_test
+            .iter()
+            .filter_map(|(k, v)| {
+                let mut times = v
+                    .iter()
+                    .filter(|&&(r, _)| r < self.earlier)
+                    .map(|&(r, i)| &self.runs[r].tests[i])
+                    .filter(|o| o.passed)
+                    .map(|o| o.time)
+                    .collect::<Vec<_>>();
+                if times.len() < MIN_RUNS {
+                    return None;
+                }
+                times.sort_by(f32::total_cmp);
+                Some((k.clone(), times[times.len() / 2]))
+            })
+            .collect();
+    }
+
+    fn push(&mut self, run: Run) {
+        let r = self.runs.len();
+        for (i, t) in run.tests.iter().enumerate() {
+            self.by_test
+                .entry((t.suite.clone(), t.name.clone()))
+                .or_default()
+                .push((r, i));
+        }
+        self.runs.push(run);
+    }
+
+    /// of the passing durations of earlier runs
+    pub fn median(&self, suite: &str, name: &str) -> Option<f32> {
+        self.medians
+            .get(&(suite.to_string(), name.to_string()))
+            .copied()
+    }
+
+    fn outcomes<'a>(
+        &'a self,
+        suite: &str,
+        name: &str,
+    ) -> impl Iterator<Item = (&'a str, &'a Outcome)> {
+        self.by_test
+            .get(&(suite.to_string(), name.to_string()))
+            .into_iter()
+            .flatten()
+            .map(|&(r, i)| (&*self.runs[r].commit, &self.runs[r].tests[i]))
+    }
+
+    /// if it passed, for its last `n` runs, oldest first
+    pub fn recent(&self, suite: &str, name: &str, n: usize) -> Vec<bool> {
+        let all = self
+            .outcomes(suite, name)
+            .map(|(_, o)| o.passed)
+            .collect::<Vec<_>>();
+        all[all.len().saturating_sub(n)..].to_vec()
+    }
+
+    /// has both passed and failed on the same commit
+    pub fn is_flaky(&self, suite: &str, name: &str) -> bool {
+        let mut seen = HashMap::<&str, bool>::new();
+        self.outcomes(suite, name)
+            .any(|(commit, o)| *seen.entry(commit).or_insert(o.passed) != o.passed)
+    }
+
+    /// appends a run to the store
+    pub fn save(&mut self, tests: Vec<Outcome>) -> Result<()> {
+        if tests.is_empty() {
+            return Ok(());
+        }
+        let run = Run {
+            commit: self.commit.clone(),
+            at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs(),
+            tests,
+        };
+        std::fs::create_dir_all(self.path.parent().unwrap())?;
+        let line = serde_json::to_string(&run)?;
+        self.push(run);
+        if self.runs.len() > KEEP {
+            // rewrite without the oldest
+            let mut runs = std::mem::take(&mut self.runs);
+            let dropped = runs.len() - KEEP;
+            let runs = runs.split_off(dropped);
+            self.earlier = self.earlier.saturating_sub(dropped);
+            self.by_test.clear();
+            let mut out = String::new();
+            for run in runs {
+                out.push_str(&serde_json::to_string(&run)?);
+                out.push('\n');
+                self.push(run);
+            }
+            self.medians();
+            std::fs::write(&self.path, out)?;
+            return Ok(());
+        }
+        let mut f = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(f, "{line}")?;
+        Ok(())
+    }
+}
+
+/// `HEAD`, with a hash of the uncommitted changes if there are any,
+/// so edits dont make a test look flaky
+fn commit(root: &Path) -> String {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(root)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| o.stdout)
+    };
+    let Some(head) = git(&["rev-parse", "HEAD"]) else {
+        return "none".to_string();
+    };
+    let head = String::from_utf8_lossy(&head).trim().to_string();
+    match git(&["diff", "HEAD"]) {
+        Some(diff) if !diff.is_empty() => {
+            let mut h = std::collections::hash_map::DefaultHasher::new();
+            diff.hash(&mut h);
+            format!("{head}+{:x}", h.finish())
+        }
+        _ => head,
+    }
+}
@@ -1,145 +1,915 @@
-mod ui;
+pub mod columns;
+pub mod filter;
+pub mod history;
+pub mod panic;
+pub mod stress;
+pub mod timeline;
+pub mod timeout;
+mod tree;
+pub mod ui;
+pub mod usage;
 use anyhow::Result;
 use cargo_metadata::libtest::SuiteEvent;
 use cargo_metadata::TestMessage as RTestMessage;
-use crossbeam::channel::Receiver;
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::prelude::*;
-use ratatui::Terminal;
-use std::ops::ControlFlow;
-use std::path::Path;
-use std::time::{Duration, Instant};
-
-use crate::cargo;
-use crate::cargo::{test, TestEvent, TestMessage};
+use crossterm::event::KeyCode;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::cargo::TestEvent;
 use crate::test::ui::stdout::Stdout;
+use crate::ui::SList;
+use ratatui::style::{Style, Stylize};
 
 #[derive(Default, PartialEq, Eq)]
 pub enum Screen {
     #[default]
     Main,
     Stdout,
+    /// the parsed panic, with a diff
+    Panic,
+    /// the frames of the panic
+    Backtrace,
+    /// one test, over and over
+    Stress,
+    /// what ran when, and next to what
+    Timeline,
+    /// memory and cpu of the test binaries
+    Usage,
+}
+
+#[derive(Default, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    List,
+    /// split on `::`
+    Tree,
+}
+
+/// how the tests are shown
+pub struct Options {
+    pub columns: Vec<columns::Column>,
+    pub threshold: history::Threshold,
+    pub timeouts: timeout::Timeouts,
+    /// times to run a failed test again
+    pub retries: usize,
+    pub slow: Slow,
+}
+
+/// seconds a test can run for before it is highlighted
+#[derive(Clone, Copy)]
+pub struct Slow {
+    pub warn: f32,
+    pub critical: f32,
+}
+
+impl Default for Slow {
+    fn default() -> Self {
+        Self {
+            warn: 5.,
+            critical: 30.,
+        }
+    }
 }
 
+#[derive(Default)]
 pub struct TestState {
     tests: Vec<TestEvent>, // use the event like a state (ok => in progress, ..)
     test_list: ui::test_list::TestList,
-    rx: Receiver<TestMessage>,
+    view: View,
+    tree: SList,
+    /// modules the user opened or closed
+    open: HashMap<String, bool>,
+    filter: filter::Filter,
+    /// indices of the tests that get through the filter, in order, see [`TestState::refresh`]
+    visible: Vec<usize>,
+    /// the tests changed since [`TestState::refresh`]
+    stale: bool,
+    columns: Vec<columns::Column>,
+    sort: columns::Sort,
+    /// the names cargo gave the suites, in order
+    suites: Vec<String>,
+    /// how many suites have started
+    started: usize,
+    /// the suite events are from, when they dont say
+    current: usize,
+    /// index into the suites, of each test.
+    /// names are only unique within a suite, so everything else goes by the index into the tests.
+    suite_of: Vec<usize>,
     screen: Screen,
+    /// the test the stdout, panic or backtrace is of
+    opened: Option<usize>,
     test_count: usize,
     stdout: Stdout,
     time: f32,
-    done: bool,
+    /// when the tests started running each in its own process
+    pool_at: Option<Instant>,
+    frames: SList,
+    /// show std frames too
+    all_frames: bool,
+    /// where paths in backtraces are relative to
+    root: PathBuf,
+    history: history::History,
+    threshold: history::Threshold,
+    /// of this run, for the history
+    outcomes: Vec<history::Outcome>,
+    stress: Option<stress::Stress>,
+    timeouts: timeout::Timeouts,
+    /// when the running tests started
+    started_at: HashMap<usize, Instant>,
+    /// running tests libtest warned have been going for over a minute
+    warned: HashSet<usize>,
+    retries: usize,
+    slow: Slow,
+    /// the failed attempts of tests that were retried, oldest first
+    attempts: HashMap<usize, Vec<TestEvent>>,
+    /// this run retries failures
+    retrying: bool,
+    timeline: timeline::Timeline,
+    usage: usage::Usage,
+    /// how the processes of tests that ran alone ended
+    exits: HashMap<usize, crate::runner::Exit>,
+    /// a test to stress, for the app to pick up
+    pub stress_request: Option<String>,
+    /// a file, line and column to open in the editor, for the app to pick up
+    pub edit: Option<(PathBuf, u32, u32)>,
+    pub done: bool,
+    /// tests to run again, for the app to pick up
+    pub rerun: Option<Vec<String>>,
+    /// this run is a rerun, its tests are already counted
+    rerunning: bool,
 }
 
 impl TestState {
-    pub fn new(dir: Option<&Path>) -> Result<Self> {
-        log::info!("initializing test state");
-        let rx = test(dir)?;
-        Ok(Self {
-            test_list: ui::test_list::TestList::default(),
-            tests: vec![],
-            rx,
-            screen: Screen::default(),
-            done: false,
-            test_count: 0,
-            time: 0.,
-            stdout: Stdout::default(),
-        })
-    }
-
-    pub fn recv(&mut self) {
-        if self.done {
+    pub fn new(opts: Options, root: PathBuf, history: history::History) -> Self {
+        Self {
+            columns: opts.columns,
+            threshold: opts.threshold,
+            timeouts: opts.timeouts,
+            retries: opts.retries,
+            slow: opts.slow,
+            root,
+            history,
+            ..Self::default()
+        }
+    }
+
+    /// cargo is about to run a suite
+    pub fn suite(&mut self, name: String) {
+        self.suites.push(name);
+    }
+
+    /// the next events are from the suite `name`, which we run ourselves
+    pub fn running(&mut self, name: String) {
+        self.current = self.suite_index(name);
+    }
+
+    /// of the suite `name`, added if its new
+    fn suite_index(&mut self, name: String) -> usize {
+        match self.suites.iter().position(|s| *s == name) {
+            Some(i) => i,
+            None => {
+                self.suites.push(name);
+                self.suites.len() - 1
+            }
+        }
+    }
+
+    /// the test `name` in the suite `suite`
+    fn find(&self, suite: usize, name: &str) -> Option<usize> {
+        (0..self.tests.len()).find(|&i| self.suite_of[i] == suite && self.tests[i].name() == name)
+    }
+
+    /// call every frame
+    pub fn tick(&mut self) {
+        if let Some(s) = &mut self.stress {
+            s.recv();
+        }
+    }
+
+    /// looks at the test binaries in `/proc`, call every frame
+    pub fn sample(&mut self, binaries: &[crate::runner::TestBinary], cwd: &std::path::Path) {
+        self.usage.sample(binaries, cwd);
+    }
+
+    /// the test in `suite` whose process had the most memory, when they ran alone
+    pub fn biggest(&self, suite: &str) -> Option<(&str, u64)> {
+        self.exits
+            .iter()
+            .filter(|&(&i, _)| self.suite_name(i) == suite)
+            .filter_map(|(&i, e)| Some((self.tests[i].name(), e.peak_rss?)))
+            .max_by_key(|&(_, kb)| kb)
+    }
+
+    /// shows a stress run
+    pub fn stress(&mut self, s: stress::Stress) {
+        self.stress = Some(s);
+        self.screen = Screen::Stress;
+    }
+
+    /// the run is over
+    pub fn finished(&mut self) {
+        self.done = true;
+        if let Some(at) = self.pool_at.take() {
+            self.time += at.elapsed().as_secs_f32();
+        }
+        // a retried test counts once, as it ended up
+        let mut seen = HashSet::new();
+        let mut outcomes = std::mem::take(&mut self.outcomes);
+        outcomes.reverse();
+        outcomes.retain(|o| seen.insert((o.suite.clone(), o.name.clone())));
+        outcomes.reverse();
+        if let Err(e) = self.history.save(outcomes) {
+            log::error!("couldnt save the history: {e}");
+        }
+    }
+
+    /// running tests that are past their timeout
+    pub fn hung(&self) -> Vec<String> {
+        self.started_at
+            .iter()
+            .map(|(&i, at)| (self.tests[i].name(), at))
+            .filter(|(name, at)| {
+                self.timeouts
+                    .of(name)
+                    .is_some_and(|t| at.elapsed().as_secs_f32() > t)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// seconds since a running test started
+    fn elapsed(&self, i: usize) -> Option<f32> {
+        Some(self.started_at.get(&i)?.elapsed().as_secs_f32())
+    }
+
+    /// yellow past the warning, red past critical
+    fn elapsed_style(&self, secs: f32) -> Style {
+        match secs {
+            s if s >= self.slow.critical => Style::default().red().bold(),
+            s if s >= self.slow.warn => Style::default().yellow(),
+            _ => Style::default().dark_gray(),
+        }
+    }
+
+    /// running tests past the warning, the longest first
+    fn slow_running(&self) -> Vec<(usize, f32)> {
+        let mut v = (0..self.tests.len())
+            .filter(|&i| matches!(self.tests[i], TestEvent::Started { .. }))
+            .filter_map(|i| Some((i, self.elapsed(i)?)))
+            .filter(|&(_, secs)| secs >= self.slow.warn)
+            .collect::<Vec<_>>();
+        v.sort_by(|a, b| b.1.total_cmp(&a.1));
+        v
+    }
+
+    /// the binary running `name` was killed, and it had `listed`.
+    /// returns the tests it didnt get to finish.
+    pub fn timed_out(&mut self, name: &str, listed: &[String]) -> Vec<String> {
+        if let Some(i) = self.find(self.current, name) {
+            self.killed(i);
+        }
+        let left = listed
+            .iter()
+            .filter(|&n| n != name)
+            .filter(|n| {
+                self.find(self.current, n)
+                    .is_none_or(|i| self.started_at.contains_key(&i))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for n in &left {
+            let Some(i) = self.find(self.current, n) else {
+                continue;
+            };
+            self.started_at.remove(&i);
+            self.warned.remove(&i);
+            self.timeline.ended(i, timeline::Outcome::Killed);
+        }
+        left
+    }
+
+    /// the test was killed for running past its timeout, the only way a test times out
+    fn killed(&mut self, i: usize) {
+        self.stale = true;
+        let name = self.tests[i].name().to_string();
+        log::warn!("{name} timed out");
+        let time = self.elapsed(i).unwrap_or(0.);
+        self.record(i, false, time);
+        self.started_at.remove(&i);
+        self.warned.remove(&i);
+        self.timeline.ended(i, timeline::Outcome::Failed);
+        self.tests[i] = TestEvent::Timeout { name };
+    }
+
+    /// the binary went away under tests it hadnt finished, so they crashed with it
+    pub fn crashed(&mut self) {
+        for (i, at) in std::mem::take(&mut self.started_at) {
+            self.stale = true;
+            let name = self.tests[i].name().to_string();
+            log::warn!("{name} never finished");
+            let time = at.elapsed().as_secs_f32();
+            self.record(i, false, time);
+            self.warned.remove(&i);
+            self.timeline.ended(i, timeline::Outcome::Killed);
+            self.tests[i] = TestEvent::Failed {
+                name,
+                exec_time: time,
+                stdout: Some("crashed with its binary".to_string()),
+                reason: None,
+                message: None,
+            };
+        }
+    }
+
+    /// `n` tests are about to run, each in its own process
+    pub fn pool(&mut self, n: usize) {
+        if self.rerunning {
             return;
         }
-        let deadline = Instant::now() + Duration::from_millis(50);
-        while let Ok(event) = self.rx.recv_deadline(deadline) {
-            log::debug!("got event {event:?}");
-            let event = match event {
-                TestMessage::Event(e) => e,
-                TestMessage::Finished => {
-                    self.done = true;
-                    return;
-                }
-                TestMessage::CompilerEvent(e) => {
-                    unreachable!("comp module should have handled event {e:?}")
-                }
+        self.test_count += n;
+        self.pool_at = Some(Instant::now());
+    }
+
+    /// an event from a test running in its own process, in the binary `suite`
+    pub fn isolated(&mut self, suite: String, event: RTestMessage) {
+        // every process is a suite of one, the pool counted them already
+        let RTestMessage::Test(t) = event else { return };
+        let suite = self.suite_index(suite);
+        self.test(suite, t);
+    }
+
+    /// the process of a test that ran alone in `suite` ended
+    pub fn exited(&mut self, suite: String, name: String, exit: crate::runner::Exit) {
+        log::debug!("{name} exited: {exit:?}");
+        let suite = self.suite_index(suite);
+        let Some(i) = self.find(suite, &name) else {
+            return log::warn!("{name} exited without starting");
+        };
+        if exit.timed_out && matches!(self.tests[i], TestEvent::Started { .. }) {
+            self.killed(i);
+        }
+        self.exits.insert(i, exit);
+        self.stale = true;
+    }
+
+    /// failed tests with retries left, which the next run retries
+    pub fn retry(&mut self) -> Vec<String> {
+        let names = (0..self.tests.len())
+            .filter(|&i| {
+                matches!(
+                    self.tests[i],
+                    TestEvent::Failed { .. } | TestEvent::Timeout { .. }
+                )
+            })
+            .filter(|i| self.attempts.get(i).map_or(0, Vec::len) < self.retries)
+            .map(|i| self.tests[i].name().to_string())
+            .collect::<Vec<_>>();
+        if !names.is_empty() {
+            log::info!("retrying {} tests", names.len());
+            self.rerunning = true;
+            self.retrying = true;
+        }
+        names
+    }
+
+    /// passed, but only after failing
+    pub fn flaky_passed(&self, i: usize) -> bool {
+        matches!(self.tests[i], TestEvent::Ok { .. }) && self.attempts.contains_key(&i)
+    }
+
+    /// the stdout of every attempt, if it was retried
+    fn output(&self, i: usize) -> Option<String> {
+        let t = &self.tests[i];
+        let Some(attempts) = self.attempts.get(&i) else {
+            return t.stdout().map(String::from);
+        };
+        let mut out = String::new();
+        for (i, a) in attempts.iter().chain([t]).enumerate() {
+            let how = match a {
+                TestEvent::Ok { .. } => "passed",
+                TestEvent::Failed { .. } => "failed",
+                TestEvent::Timeout { .. } => "timed out",
+                _ => "running",
             };
-            match event {
-                RTestMessage::Test(t) => match t {
-                    TestEvent::Started { name } => {
-                        self.tests.push(TestEvent::Started { name });
-                    }
-                    t => {
-                        let i = self
-                            .tests
-                            .iter()
-                            .position(|o| o.name() == t.name())
-                            .unwrap();
-                        self.tests[i] = t;
+            out.push_str(&format!("── attempt {} {how} ──\n", i + 1));
+            out.push_str(a.stdout().unwrap_or(""));
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+        Some(out)
+    }
+
+    /// the next events finish a run thats already counted
+    pub fn resume(&mut self) {
+        self.rerunning = true;
+    }
+
+    /// if a test passed, its last few runs, and if its flaky
+    fn history(&self, i: usize) -> (Vec<bool>, bool) {
+        let (suite, test) = (self.suite_name(i), self.tests[i].name());
+        (
+            self.history.recent(suite, test, history::RECENT),
+            self.history.is_flaky(suite, test),
+        )
+    }
+
+    /// the median duration of earlier runs, and this one
+    fn delta(&self, i: usize) -> Option<(f32, f32)> {
+        let t = &self.tests[i];
+        let now = match t {
+            TestEvent::Ok { exec_time, .. } | TestEvent::Failed { exec_time, .. } => *exec_time,
+            _ => return None,
+        };
+        let median = self.history.median(self.suite_name(i), t.name())?;
+        Some((median, now))
+    }
+
+    /// tests that got slower, the worst first
+    fn regressions(&self) -> Vec<(usize, f32, f32)> {
+        let mut v = (0..self.tests.len())
+            .filter_map(|i| {
+                let (median, now) = self.delta(i)?;
+                self.threshold
+                    .regressed(median, now)
+                    .then_some((i, median, now))
+            })
+            .collect::<Vec<_>>();
+        v.sort_by(|a, b| (b.2 / b.1).total_cmp(&(a.2 / a.1)));
+        v
+    }
+
+    /// red if it got slower, green if faster
+    fn duration_style(&self, i: usize) -> Style {
+        match self.delta(i) {
+            Some((m, n)) if self.threshold.regressed(m, n) => Style::default().red(),
+            Some((m, n)) if self.threshold.improved(m, n) => Style::default().green(),
+            _ => Style::default(),
+        }
+    }
+
+    /// how a test ended, for the history
+    fn record(&mut self, i: usize, passed: bool, time: f32) {
+        self.outcomes.push(history::Outcome {
+            suite: self.suite_name(i).to_string(),
+            name: self.tests[i].name().to_string(),
+            passed,
+            time,
+        });
+    }
+
+    /// the suite a test ran in
+    fn suite_name(&self, i: usize) -> &str {
+        self.suites
+            .get(self.suite_of[i])
+            .map_or("?", String::as_str)
+    }
+
+    /// an event of a test in the suite `suite`
+    fn test(&mut self, suite: usize, t: TestEvent) {
+        self.stale = true;
+        let found = self.find(suite, t.name());
+        match t {
+            TestEvent::Started { name } => {
+                let i = match found {
+                    Some(i)
+                        if self.retrying
+                            && matches!(
+                                self.tests[i],
+                                TestEvent::Failed { .. } | TestEvent::Timeout { .. }
+                            ) =>
+                    {
+                        let failed =
+                            std::mem::replace(&mut self.tests[i], TestEvent::Started { name });
+                        self.attempts.entry(i).or_default().push(failed);
+                        i
                     }
-                },
-                RTestMessage::Suite(s) => match s {
-                    SuiteEvent::Ok { exec_time, .. } | SuiteEvent::Failed { exec_time, .. } => {
-                        self.time += exec_time;
+                    Some(i) => {
+                        self.tests[i] = TestEvent::Started { name };
+                        i
                     }
-                    SuiteEvent::Started { test_count } => {
-                        log::trace!("have {test_count} tests");
-                        self.test_count += test_count;
+                    None => {
+                        self.suite_of.push(suite);
+                        self.tests.push(TestEvent::Started { name });
+                        self.tests.len() - 1
                     }
-                },
-                RTestMessage::Bench { .. } => unreachable!("not applicable"),
-            };
+                };
+                self.started_at.insert(i, Instant::now());
+                self.timeline.started(i, self.tests[i].name());
+            }
+            t => {
+                let Some(i) = found else {
+                    return log::warn!("{} ended without starting", t.name());
+                };
+                // libtests own timeout is only a warning, it keeps running
+                if let TestEvent::Timeout { .. } = t {
+                    self.warned.insert(i);
+                    return;
+                }
+                self.started_at.remove(&i);
+                self.warned.remove(&i);
+                self.timeline.ended(i, timeline::Outcome::of(&t));
+                if let TestEvent::Ok { .. } | TestEvent::Failed { .. } = t {
+                    self.record(i, matches!(t, TestEvent::Ok { .. }), columns::duration(&t));
+                }
+                self.tests[i] = t;
+            }
         }
     }
-}
 
-pub fn run<B: Backend>(
-    terminal: &mut Terminal<B>,
-    dir: Option<&Path>,
-    meta: &cargo::Metadata,
-) -> Result<()> {
-    let mut state = TestState::new(dir)?;
-    match crate::compiler::run(terminal, meta, state.rx)? {
-        ControlFlow::Break(()) => return Ok(()),
-        ControlFlow::Continue(rx) => {
-            state.rx = rx;
-        }
-    }
-    print!("\x1b]0;testing {}\x07", meta.package.name);
-    loop {
-        terminal.draw(|f| ui::ui(f, &mut state, meta))?;
-        if event::poll(Duration::from_millis(5))? {
-            if let Event::Key(key) = event::read()? {
-                match state.screen {
-                    Screen::Main => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Down | KeyCode::Char('s') => state.test_list.next(),
-                        KeyCode::Up | KeyCode::Char('w') => state.test_list.prev(),
-                        KeyCode::Right | KeyCode::Char('d')
-                            if state.test_list.stdout(&state).is_some() =>
-                        {
-                            state.screen = Screen::Stdout;
-                            state.stdout.scroll = 0;
-                            state.stdout.lines = u16::try_from(
-                                state.test_list.stdout(&state).unwrap().lines().count(),
-                            )?;
-                        }
-                        _ => {}
-                    },
-                    Screen::Stdout => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Down | KeyCode::Char('s') => state.stdout.incr(),
-                        KeyCode::Up | KeyCode::Char('w') => state.stdout.decr(),
-                        KeyCode::Left | KeyCode::Char('a') => {
-                            state.screen = Screen::Main;
-                            state.stdout.scroll = 0;
+    pub fn handle(&mut self, event: RTestMessage) {
+        log::debug!("got event {event:?}");
+        match event {
+            RTestMessage::Test(t) => self.test(self.current, t),
+            RTestMessage::Suite(s) => match s {
+                SuiteEvent::Ok { .. } | SuiteEvent::Failed { .. } if self.rerunning => {}
+                SuiteEvent::Ok { exec_time, .. } | SuiteEvent::Failed { exec_time, .. } => {
+                    self.time += exec_time;
+                    self.crashed();
+                }
+                // binaries run one after another, so the last one is gone
+                SuiteEvent::Started { .. } if self.rerunning => self.crashed(),
+                SuiteEvent::Started { test_count } => {
+                    self.crashed();
+                    log::trace!("have {test_count} tests");
+                    self.test_count += test_count;
+                    self.current = self.started;
+                    self.started += 1;
+                }
+            },
+            RTestMessage::Bench { .. } => unreachable!("not applicable"),
+        };
+    }
+
+    /// filters and sorts the tests again, after they or the filter or sort change
+    fn refresh(&mut self) {
+        let mut v = (0..self.tests.len())
+            .filter(|&i| {
+                self.filter
+                    .matches(&self.tests[i], self.slow.warn, self.elapsed(i))
+            })
+            .map(|i| (self.sort.key(self, i), i))
+            .collect::<Vec<_>>();
+        v.sort_by(|(a, _), (b, _)| self.sort.cmp(a, b));
+        self.visible = v.into_iter().map(|(_, i)| i).collect();
+    }
+
+    fn rows(&self) -> Vec<tree::Row> {
+        tree::rows(&self.tests, &self.visible, &self.open)
+    }
+
+    /// the search has the keyboard
+    pub fn typing(&self) -> bool {
+        self.filter.typing
+    }
+
+    /// the name of whats selected, test or module
+    fn selected_path(&self) -> Option<String> {
+        match self.view {
+            View::List => self.selected().map(|i| self.tests[i].name().to_string()),
+            View::Tree => Some(self.rows().get(self.tree.state.selected()?)?.path.clone()),
+        }
+    }
+
+    /// changes the filter, keeping the selection where it can
+    fn refilter(&mut self, f: impl FnOnce(&mut filter::Filter)) {
+        self.reselect(|s| f(&mut s.filter));
+    }
+
+    /// changes what is shown, keeping the selection where it can
+    fn reselect(&mut self, f: impl FnOnce(&mut Self)) {
+        let (test, path) = (self.selected(), self.selected_path());
+        f(self);
+        self.refresh();
+        let at = match self.view {
+            View::List => test.and_then(|t| self.visible.iter().position(|&i| i == t)),
+            View::Tree => path.and_then(|path| self.rows().iter().position(|r| r.path == path)),
+        };
+        match self.view {
+            View::List => self.test_list.select(at),
+            View::Tree => self.tree.state.select(at),
+        }
+    }
+
+    fn search(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) => self.refilter(|f| f.query.push(c)),
+            KeyCode::Backspace => self.refilter(|f| _ = f.query.pop()),
+            KeyCode::Enter => self.filter.typing = false,
+            KeyCode::Esc => self.refilter(|f| {
+                f.query.clear();
+                f.typing = false;
+            }),
+            _ => {}
+        }
+    }
+
+    /// toggles showing only some tests
+    fn only(&mut self, only: filter::Only) {
+        self.refilter(|f| {
+            f.only = if f.only == only {
+                filter::Only::All
+            } else {
+                only
+            }
+        });
+    }
+
+    /// index into the tests
+    pub fn selected(&self) -> Option<usize> {
+        match self.view {
+            View::List => self.test_list.selects(self),
+            View::Tree => match self.rows().get(self.tree.state.selected()?)?.kind {
+                tree::Kind::Test(i) => Some(i),
+                tree::Kind::Module { .. } => None,
+            },
+        }
+    }
+
+    /// the test the screen is about, the one it was opened on if it has its own
+    fn shown(&self) -> Option<usize> {
+        match self.screen {
+            Screen::Stdout | Screen::Panic | Screen::Backtrace => self.opened,
+            _ => self.selected(),
+        }
+    }
+
+    /// shows `screen` for the selected test, which it sticks to
+    fn open(&mut self, screen: Screen) {
+        self.opened = self.shown();
+        self.screen = screen;
+    }
+
+    /// catches up before a frame or key: filters the tests again if they changed,
+    /// and goes back to the list if the test a screen was opened on is gone
+    pub fn settle(&mut self) {
+        // running tests become slow without an event saying so
+        if self.stale || self.filter.only == filter::Only::Slow {
+            self.stale = false;
+            self.refresh();
+        }
+        let gone = match self.screen {
+            Screen::Stdout => self.shown().and_then(|i| self.output(i)).is_none(),
+            Screen::Panic => self.panic().is_none(),
+            Screen::Backtrace => self.panic().is_none_or(|p| p.backtrace.is_empty()),
+            _ => false,
+        };
+        if gone {
+            self.screen = Screen::Main;
+            self.opened = None;
+            self.frames.state.select(None);
+        }
+    }
+
+    /// why the shown test failed
+    fn panic(&self) -> Option<panic::Panic> {
+        match &self.tests[self.shown()?] {
+            TestEvent::Failed {
+                name,
+                stdout: Some(stdout),
+                ..
+            } => panic::of(panic::parse(stdout), name),
+            _ => None,
+        }
+    }
+
+    /// opens the backtrace at the first frame of the workspace
+    fn backtrace(&mut self) {
+        let Some(p) = self.panic().filter(|p| !p.backtrace.is_empty()) else {
+            return;
+        };
+        let shown = panic::shown(&p.backtrace, self.all_frames);
+        let first = shown
+            .iter()
+            .position(|s| matches!(s, panic::Shown::Frame(i) if p.backtrace[*i].is_workspace()));
+        self.frames.state.select(first.or(Some(0)));
+        self.open(Screen::Backtrace);
+    }
+
+    /// on the selected frame
+    fn frame_key(&mut self, code: KeyCode) {
+        let Some(p) = self.panic() else { return };
+        let shown = panic::shown(&p.backtrace, self.all_frames);
+        self.frames.has(shown.len());
+        match code {
+            KeyCode::Down | KeyCode::Char('s') => self.frames.next(),
+            KeyCode::Up | KeyCode::Char('w') => self.frames.prev(),
+            KeyCode::Char('h') => {
+                self.all_frames = !self.all_frames;
+                self.backtrace();
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                match self.frames.state.selected().and_then(|i| shown.get(i)) {
+                    Some(panic::Shown::Hidden(_)) => {
+                        self.all_frames = true;
+                        self.backtrace();
+                    }
+                    Some(&panic::Shown::Frame(i)) => {
+                        if let Some((file, line, col)) = &p.backtrace[i].at {
+                            let file = self.root.join(file.trim_start_matches("./"));
+                            if file.exists() {
+                                self.edit = Some((file, *line, *col));
+                            } else {
+                                log::warn!("{} isnt there", file.display());
+                            }
                         }
-                        _ => {}
-                    },
+                    }
+                    None => {}
+                }
+            }
+            KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc => self.screen = Screen::Main,
+            _ => {}
+        }
+    }
+
+    /// selects the test in the list, clearing a filter that hides it
+    fn jump(&mut self, test: usize) {
+        self.view = View::List;
+        self.screen = Screen::Main;
+        if !self.visible.contains(&test) {
+            self.filter = filter::Filter::default();
+            self.refresh();
+        }
+        let at = self.visible.iter().position(|&i| i == test);
+        self.test_list.select(at);
+    }
+
+    fn timeline_key(&mut self, code: KeyCode) {
+        let t = &mut self.timeline;
+        match code {
+            KeyCode::Right | KeyCode::Char('d') => t.step(true),
+            KeyCode::Left | KeyCode::Char('a') => t.step(false),
+            KeyCode::Down | KeyCode::Char('s') => t.lane(true),
+            KeyCode::Up | KeyCode::Char('w') => t.lane(false),
+            KeyCode::Char('+' | '=') => t.zoom(2.),
+            KeyCode::Char('-') => t.zoom(0.5),
+            KeyCode::Enter => {
+                if let Some(test) = t.selected.map(|i| t.bars[i].test) {
+                    self.jump(test);
                 }
             }
+            KeyCode::Esc => self.screen = Screen::Main,
+            _ => {}
+        }
+    }
+
+    /// the selected module, if its one
+    fn module(&self) -> Option<(String, bool)> {
+        if self.view != View::Tree {
+            return None;
+        }
+        let rows = self.rows();
+        match rows.get(self.tree.state.selected()?)? {
+            tree::Row {
+                path,
+                kind: tree::Kind::Module { open, .. },
+                ..
+            } => Some((path.clone(), *open)),
+            _ => None,
+        }
+    }
+
+    /// the selected test, or every test under the selected module
+    fn selected_tests(&self) -> Vec<String> {
+        if let Some((path, _)) = self.module() {
+            let prefix = format!("{path}::");
+            return self
+                .tests
+                .iter()
+                .map(TestEvent::name)
+                .filter(|n| n.starts_with(&prefix))
+                .map(String::from)
+                .collect();
+        }
+        self.selected()
+            .map(|i| vec![self.tests[i].name().to_string()])
+            .unwrap_or_default()
+    }
+
+    /// asks for `names` to run again, once the current run is over
+    fn rerun(&mut self, names: Vec<String>) {
+        if !self.done || names.is_empty() {
+            return;
+        }
+        log::info!("rerunning {} tests", names.len());
+        self.rerunning = true;
+        // they get their retries again
+        self.retrying = false;
+        let tests = &self.tests;
+        self.attempts
+            .retain(|&i, _| !names.iter().any(|n| n == tests[i].name()));
+        self.rerun = Some(names);
+    }
+
+    pub fn key(&mut self, code: KeyCode) -> Result<()> {
+        if self.filter.typing {
+            self.search(code);
+            return Ok(());
+        }
+        self.settle();
+        match self.screen {
+            Screen::Main => match code {
+                KeyCode::Char('/') => self.filter.typing = true,
+                KeyCode::Char('f') => self.only(filter::Only::Failed),
+                KeyCode::Char('u') => self.only(filter::Only::Running),
+                KeyCode::Char('r') => self.rerun(self.selected_tests()),
+                KeyCode::Char('R') => self.rerun(
+                    self.tests
+                        .iter()
+                        .filter(|t| {
+                            matches!(t, TestEvent::Failed { .. } | TestEvent::Timeout { .. })
+                        })
+                        .map(|t| t.name().to_string())
+                        .collect(),
+                ),
+                KeyCode::Char('i') => self.only(filter::Only::Ignored),
+                KeyCode::Char('o') => self.only(filter::Only::Slow),
+                KeyCode::Esc if self.filter.is_active() => {
+                    self.refilter(|f| *f = filter::Filter::default())
+                }
+                KeyCode::Char('c') => self.reselect(|s| s.sort.next(&s.columns)),
+                KeyCode::Char('v') if self.sort.by.is_some() => {
+                    self.reselect(|s| s.sort.reverse = !s.sort.reverse)
+                }
+                KeyCode::Char('t') => {
+                    self.view = match self.view {
+                        View::List => View::Tree,
+                        View::Tree => View::List,
+                    }
+                }
+                KeyCode::Down | KeyCode::Char('s') => match self.view {
+                    View::List => self.test_list.next(),
+                    View::Tree => self.tree.next(),
+                },
+                KeyCode::Up | KeyCode::Char('w') => match self.view {
+                    View::List => self.test_list.prev(),
+                    View::Tree => self.tree.prev(),
+                },
+                KeyCode::Right | KeyCode::Char('d') | KeyCode::Enter
+                    if self.module().is_some_and(|(_, open)| !open) =>
+                {
+                    self.open.insert(self.module().unwrap().0, true);
+                }
+                KeyCode::Left | KeyCode::Char('a') | KeyCode::Enter
+                    if self.module().is_some_and(|(_, open)| open) =>
+                {
+                    self.open.insert(self.module().unwrap().0, false);
+                }
+                KeyCode::Right | KeyCode::Char('d')
+                    if self.selected().and_then(|i| self.output(i)).is_some() =>
+                {
+                    self.open(Screen::Stdout);
+                    self.stdout.scroll = 0;
+                    let out = self.shown().and_then(|i| self.output(i)).unwrap();
+                    self.stdout.lines = u16::try_from(out.lines().count())?;
+                }
+                KeyCode::Char('b') => self.backtrace(),
+                KeyCode::Char('l') => self.screen = Screen::Timeline,
+                KeyCode::Char('m') => self.screen = Screen::Usage,
+                KeyCode::Char('S') if self.done => {
+                    self.stress_request = self.selected().map(|i| self.tests[i].name().to_string())
+                }
+                KeyCode::Char('p') if self.panic().is_some() => {
+                    self.open(Screen::Panic);
+                    self.stdout.scroll = 0;
+                }
+                _ => {}
+            },
+            Screen::Backtrace => self.frame_key(code),
+            Screen::Timeline => self.timeline_key(code),
+            Screen::Usage => {
+                if let KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc = code {
+                    self.screen = Screen::Main;
+                }
+            }
+            Screen::Stress => {
+                if let KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc = code {
+                    // stops it
+                    self.stress = None;
+                    self.screen = Screen::Main;
+                }
+            }
+            Screen::Panic => match code {
+                KeyCode::Char('b') => self.backtrace(),
+                KeyCode::Down | KeyCode::Char('s') => self.stdout.incr(),
+                KeyCode::Up | KeyCode::Char('w') => self.stdout.decr(),
+                KeyCode::Left | KeyCode::Char('a') | KeyCode::Esc => {
+                    self.screen = Screen::Main;
+                    self.stdout.scroll = 0;
+                }
+                _ => {}
+            },
+            Screen::Stdout => match code {
+                KeyCode::Down | KeyCode::Char('s') => self.stdout.incr(),
+                KeyCode::Up | KeyCode::Char('w') => self.stdout.decr(),
+                KeyCode::Left | KeyCode::Char('a') => {
+                    self.screen = Screen::Main;
+                    self.stdout.scroll = 0;
+                }
+                _ => {}
+            },
         }
-        state.recv();
+        Ok(())
     }
 }
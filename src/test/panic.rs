@@ -0,0 +1,392 @@
+//! picking apart libtest panic output
+pub struct Panic {
+    pub thread: String,
+    /// `src/foo.rs:12:5`
+    pub location: String,
+    pub message: String,
+    pub assert: Option<Assert>,
+    /// if `RUST_BACKTRACE` was set
+    pub backtrace: Vec<Frame>,
+}
+
+pub struct Frame {
+    pub symbol: String,
+    /// file, line, column
+    pub at: Option<(String, u32, u32)>,
+}
+
+impl Frame {
+    /// of std, core, or the test harness
+    pub fn is_std(&self) -> bool {
+        let krate = self.symbol.trim_start_matches('<');
+        let krate = krate.split("::").next().unwrap_or(krate);
+        match &self.at {
+            None => true,
+            Some((file, ..)) if file.starts_with("/rustc/") => true,
+            Some(_) => matches!(
+                krate,
+                "std" | "core" | "alloc" | "test" | "__rustc" | "panic_unwind"
+            ),
+        }
+    }
+
+    /// rustc gets relative paths for workspace members, and absolute ones for the rest
+    pub fn is_workspace(&self) -> bool {
+        self.at
+            .as_ref()
+            .is_some_and(|(file, ..)| !file.starts_with('/'))
+    }
+}
+
+/// a failed `assert_eq!` or `assert_ne!`
+pub struct Assert {
+    /// `==` or `!=`
+    pub op: &'static str,
+    pub left: String,
+    pub right: String,
+}
+
+/// a row of the frame list
+pub enum Shown {
+    /// index into the backtrace
+    Frame(usize),
+    /// this many std frames, collapsed
+    Hidden(usize),
+}
+
+/// the frames to list, with runs of std frames collapsed unless `all`
+pub fn shown(frames: &[Frame], all: bool) -> Vec<Shown> {
+    let mut v = vec![];
+    for (i, f) in frames.iter().enumerate() {
+        if all || !f.is_std() {
+            v.push(Shown::Frame(i));
+            continue;
+        }
+        match v.last_mut() {
+            Some(Shown::Hidden(n)) => *n += 1,
+            _ => v.push(Shown::Hidden(1)),
+        }
+    }
+    v
+}
+
+/// the panics in the output of a test, in order
+pub fn parse(stdout: &str) -> Vec<Panic> {
+    let mut panics = vec![];
+    let mut lines = stdout.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some((thread, location)) = header(line) else {
+            continue;
+        };
+        let mut message = vec![];
+        while let Some(&l) = lines.peek() {
+            if l.starts_with("note: ") || l.starts_with("stack backtrace:") || header(l).is_some() {
+                break;
+            }
+            message.push(l);
+            lines.next();
+        }
+        let message = message.join("\n").trim_end().to_string();
+        let mut backtrace = vec![];
+        if lines
+            .next_if(|l| l.starts_with("stack backtrace:"))
+            .is_some()
+        {
+            while let Some(symbol) = lines.peek().and_then(|l| frame(l)) {
+                lines.next();
+                let at = lines.next_if(|l| at(l).is_some()).and_then(at);
+                backtrace.push(Frame { symbol, at });
+            }
+        }
+        panics.push(Panic {
+            thread,
+            location,
+            assert: assert(&message),
+            message,
+            backtrace,
+        });
+    }
+    panics
+}
+
+/// the panic that failed the test, on its thread if its there
+pub fn of(mut panics: Vec<Panic>, test: &str) -> Option<Panic> {
+    let i = panics.iter().position(|p| p.thread == test).unwrap_or(0);
+    (i < panics.len()).then(|| panics.swap_remove(i))
+}
+
+/// `thread 'name' (1234) panicked at src/foo.rs:12:5:`
+fn header(line: &str) -> Option<(String, String)> {
+    let (thread, rest) = line.strip_prefix("thread '")?.split_once('\'')?;
+    let (_, location) = rest.split_once(" panicked at ")?;
+    Some((
+        thread.to_string(),
+        location.strip_suffix(':').unwrap_or(location).to_string(),
+    ))
+}
+
+/// `   2: demo::parser::expr::unary_ops`, or with `RUST_BACKTRACE=full`,
+/// `   2:     0x5597e82c9ef8 - std[d28b1718532fa52a]::sys::backtrace::_print_fmt`
+fn frame(line: &str) -> Option<String> {
+    let (n, symbol) = line.trim_start().split_once(": ")?;
+    n.parse::<usize>().ok()?;
+    let symbol = symbol.trim_start();
+    let symbol = match symbol.split_once(" - ") {
+        Some((addr, s)) if addr.starts_with("0x") => s,
+        _ => symbol,
+    };
+    Some(unhash(symbol))
+}
+
+/// drops the `[d28b1718532fa52a]` of `std[d28b1718532fa52a]::`
+fn unhash(symbol: &str) -> String {
+    let mut out = String::with_capacity(symbol.len());
+    let mut rest = symbol;
+    while let Some(i) = rest.find('[') {
+        out.push_str(&rest[..i]);
+        match rest[i + 1..].split_once(']') {
+            Some((h, r)) if !h.is_empty() && h.chars().all(|c| c.is_ascii_hexdigit()) => rest = r,
+            _ => {
+                out.push('[');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `             at ./src/main.rs:5:34`
+fn at(line: &str) -> Option<(String, u32, u32)> {
+    let at = line.trim_start().strip_prefix("at ")?;
+    let (rest, col) = at.rsplit_once(':')?;
+    let (file, line) = rest.rsplit_once(':')?;
+    Some((file.to_string(), line.parse().ok()?, col.parse().ok()?))
+}
+
+fn assert(message: &str) -> Option<Assert> {
+    let op = if message.starts_with("assertion `left == right` failed") {
+        "=="
+    } else if message.starts_with("assertion `left != right` failed") {
+        "!="
+    } else {
+        return None;
+    };
+    let (_, values) = message.split_once("\n  left: ")?;
+    let (left, right) = values.split_once("\n right: ")?;
+    Some(Assert {
+        op,
+        left: left.to_string(),
+        right: right.to_string(),
+    })
+}
+
+/// what a line of `Debug` output gets before its broken up
+const WIDTH: usize = 60;
+
+/// reflows one line `Debug` output like `{:#?}` would,
+/// but keeps groups that fit on one line.
+pub fn pretty(debug: &str) -> String {
+    if debug.contains('\n') {
+        return debug.to_string();
+    }
+    let mut out = String::new();
+    reflow(debug.trim(), 0, &mut out);
+    out
+}
+
+fn reflow(s: &str, depth: usize, out: &mut String) {
+    if s.len() + depth * 4 <= WIDTH {
+        out.push_str(s);
+        return;
+    }
+    let st = structure(s);
+    let mut from = 0;
+    let mut i = 0;
+    while i < st.len() {
+        let (open, c, d) = st[i];
+        i += 1;
+        if d != 0 || !matches!(c, '{' | '[' | '(') {
+            continue;
+        }
+        let Some(end) = st[i..].iter().position(|&(_, c, d)| d == 0 && is_close(c)) else {
+            break;
+        };
+        let close = st[i + end].0;
+        out.push_str(&s[from..=open]);
+        let mut start = open + 1;
+        let commas = st[i..i + end]
+            .iter()
+            .filter(|&&(_, c, d)| d == 1 && c == ',')
+            .map(|&(at, ..)| at)
+            .chain(std::iter::once(close));
+        for at in commas {
+            let part = s[start..at].trim();
+            start = at + 1;
+            if part.is_empty() {
+                continue;
+            }
+            out.push('\n');
+            out.push_str(&"    ".repeat(depth + 1));
+            reflow(part, depth + 1, out);
+            out.push(',');
+        }
+        out.push('\n');
+        out.push_str(&"    ".repeat(depth));
+        out.push_str(&s[close..=close]);
+        from = close + 1;
+        i += end + 1;
+    }
+    out.push_str(&s[from..]);
+}
+
+fn is_close(c: char) -> bool {
+    matches!(c, '}' | ']' | ')')
+}
+
+/// the brackets and commas outside of literals, with their depth
+fn structure(s: &str) -> Vec<(usize, char, usize)> {
+    let mut v = vec![];
+    let mut depth = 0_usize;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                while let Some((_, n)) = chars.next() {
+                    if n == '\\' {
+                        chars.next();
+                    } else if n == c {
+                        break;
+                    }
+                }
+            }
+            '{' | '[' | '(' => {
+                v.push((i, c, depth));
+                depth += 1;
+            }
+            c if is_close(c) => {
+                depth = depth.saturating_sub(1);
+                v.push((i, c, depth));
+            }
+            ',' => v.push((i, c, depth)),
+            _ => {}
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSERT: &str = "
+thread 'parser::eq' (31825) panicked at src/parser.rs:4:11:
+assertion `left == right` failed: points differ
+  left: P { x: 1, name: \"a\\nb\" }
+ right: P { x: 2, name: \"a\" }
+stack backtrace:
+   0: __rustc::rust_begin_unwind
+             at /rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/std/src/panicking.rs:689:5
+   1: core::panicking::panic_fmt
+             at /rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/panicking.rs:80:14
+   2: demo::parser::eq
+             at ./src/parser.rs:4:11
+   3: demo::parser::eq::{{closure}}
+             at ./src/parser.rs:4:8
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+";
+
+    #[test]
+    fn eq_assert() {
+        let [p] = &parse(ASSERT)[..] else {
+            panic!("one panic")
+        };
+        assert_eq!(p.thread, "parser::eq");
+        assert_eq!(p.location, "src/parser.rs:4:11");
+        let a = p.assert.as_ref().unwrap();
+        assert_eq!(a.op, "==");
+        assert_eq!(a.left, "P { x: 1, name: \"a\\nb\" }");
+        assert_eq!(a.right, "P { x: 2, name: \"a\" }");
+        let symbols = p.backtrace.iter().map(|f| &*f.symbol).collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            [
+                "__rustc::rust_begin_unwind",
+                "core::panicking::panic_fmt",
+                "demo::parser::eq",
+                "demo::parser::eq::{{closure}}"
+            ]
+        );
+        assert_eq!(
+            p.backtrace[2].at,
+            Some(("./src/parser.rs".to_string(), 4, 11))
+        );
+        assert!(p.backtrace[1].is_std() && !p.backtrace[2].is_std());
+    }
+
+    #[test]
+    fn multi_line_assert() {
+        let out = "
+thread 'multi' (31889) panicked at src/lib.rs:7:14:
+assertion `left != right` failed
+  left: M {
+    a: 1,
+}
+ right: M {
+    a: 1,
+}
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+";
+        let p = of(parse(out), "multi").unwrap();
+        assert!(p.backtrace.is_empty());
+        let a = p.assert.unwrap();
+        assert_eq!(a.op, "!=");
+        assert_eq!(a.left, "M {\n    a: 1,\n}");
+        assert_eq!(a.right, "M {\n    a: 1,\n}");
+        // already broken up
+        assert_eq!(pretty(&a.left), a.left);
+    }
+
+    #[test]
+    fn plain_panics() {
+        let out = "
+thread 'plain' (31826) panicked at src/lib.rs:6:14:
+boom
+second line
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+thread '<unnamed>' (31830) panicked at src/lib.rs:9:9:
+from a thread
+";
+        let panics = parse(out);
+        assert_eq!(panics.len(), 2);
+        assert_eq!(panics[0].message, "boom\nsecond line");
+        assert!(panics[0].assert.is_none());
+        assert_eq!(panics[1].thread, "<unnamed>");
+        assert_eq!(of(panics, "plain").unwrap().location, "src/lib.rs:6:14");
+    }
+
+    #[test]
+    fn full_backtrace() {
+        assert_eq!(
+            frame("   2:     0x5597e82c9ef8 - std[d28b1718532fa52a]::sys::backtrace::_print_fmt"),
+            Some("std::sys::backtrace::_print_fmt".to_string())
+        );
+        assert_eq!(unhash("<[u8]>::len"), "<[u8]>::len");
+    }
+
+    #[test]
+    fn long_debug() {
+        assert_eq!(pretty("P { x: 1 }"), "P { x: 1 }");
+        assert_eq!(
+            pretty(
+                r#"Config { name: "a, b", paths: ["src/lib.rs", "src/main.rs"], jobs: Some(4) }"#
+            ),
+            r#"Config {
+    name: "a, b",
+    paths: ["src/lib.rs", "src/main.rs"],
+    jobs: Some(4),
+}"#
+        );
+    }
+}
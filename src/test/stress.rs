@@ -0,0 +1,115 @@
+//! running one test over and over, for flakiness
+use crossbeam::channel::{Receiver, TryRecvError};
+
+use super::panic;
+
+/// one run of the test
+pub struct Sample {
+    pub passed: bool,
+    /// seconds
+    pub time: f32,
+    pub stdout: Option<String>,
+}
+
+pub struct Stress {
+    pub name: String,
+    pub runs: usize,
+    /// of each run, in the order they finished
+    pub times: Vec<f32>,
+    pub passed: usize,
+    /// distinct failures, with how often they happened
+    pub failures: Vec<(String, usize)>,
+    rx: Receiver<Sample>,
+    /// the runner stopped, maybe early
+    finished: bool,
+}
+
+impl Stress {
+    pub fn new(name: String, runs: usize, rx: Receiver<Sample>) -> Self {
+        Self {
+            name,
+            runs,
+            times: vec![],
+            passed: 0,
+            failures: vec![],
+            rx,
+            finished: false,
+        }
+    }
+
+    /// call every frame
+    pub fn recv(&mut self) {
+        loop {
+            let s = match self.rx.try_recv() {
+                Ok(s) => s,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
+                }
+            };
+            self.times.push(s.time);
+            if s.passed {
+                self.passed += 1;
+                continue;
+            }
+            let why = self.why(s.stdout.as_deref());
+            match self.failures.iter_mut().find(|(w, _)| *w == why) {
+                Some((_, n)) => *n += 1,
+                None => self.failures.push((why, 1)),
+            }
+        }
+        self.failures.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    }
+
+    /// the panic message and where, or the last thing it printed
+    fn why(&self, stdout: Option<&str>) -> String {
+        let stdout = stdout.unwrap_or("");
+        match panic::of(panic::parse(stdout), &self.name) {
+            Some(p) => format!("{} at {}", p.message, p.location),
+            None => stdout
+                .lines()
+                .rev()
+                .find(|l| !l.trim().is_empty())
+                .unwrap_or("failed without output")
+                .to_string(),
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done() >= self.runs || self.finished
+    }
+
+    /// 0 to 1
+    pub fn pass_rate(&self) -> f32 {
+        self.passed as f32 / self.done().max(1) as f32
+    }
+
+    /// `q` from 0 to 1
+    pub fn percentile(&self, q: f32) -> Option<f32> {
+        let mut t = self.times.clone();
+        t.sort_by(f32::total_cmp);
+        let i = ((t.len().checked_sub(1)?) as f32 * q).round() as usize;
+        t.get(i).copied()
+    }
+
+    /// `n` equal buckets from the fastest to the slowest run, as (lower bound, count)
+    pub fn histogram(&self, n: usize) -> Vec<(f32, u64)> {
+        let (Some(min), Some(max)) = (self.percentile(0.), self.percentile(1.)) else {
+            return vec![];
+        };
+        let width = ((max - min) / n as f32).max(f32::EPSILON);
+        let mut h = (0..n)
+            .map(|i| (min + width * i as f32, 0))
+            .collect::<Vec<_>>();
+        for &t in &self.times {
+            let i = (((t - min) / width) as usize).min(n - 1);
+            h[i].1 += 1;
+        }
+        h
+    }
+}
@@ -0,0 +1,172 @@
+//! when each test ran, and next to what, by when its events arrived
+use std::time::Instant;
+
+use crate::cargo::TestEvent;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Running,
+    Passed,
+    Failed,
+    Ignored,
+    /// with the binary, when another test timed out or it crashed
+    Killed,
+}
+
+impl Outcome {
+    pub fn of(t: &TestEvent) -> Self {
+        match t {
+            TestEvent::Started { .. } => Outcome::Running,
+            TestEvent::Ok { .. } => Outcome::Passed,
+            TestEvent::Ignored { .. } => Outcome::Ignored,
+            TestEvent::Failed { .. } | TestEvent::Timeout { .. } => Outcome::Failed,
+        }
+    }
+}
+
+pub struct Bar {
+    /// index into the tests
+    pub test: usize,
+    pub name: String,
+    /// seconds since the first test started
+    pub start: f32,
+    pub end: Option<f32>,
+    /// the slot it ran in, the lowest one free when it started
+    pub lane: usize,
+    pub outcome: Outcome,
+}
+
+#[derive(Default)]
+pub struct Timeline {
+    began: Option<Instant>,
+    /// in the order they started
+    pub bars: Vec<Bar>,
+    /// if each lane has a test running in it
+    busy: Vec<bool>,
+    /// how many times closer than the whole run
+    pub zoom: f32,
+    /// seconds cut off on the left
+    pub offset: f32,
+    /// index into the bars
+    pub selected: Option<usize>,
+}
+
+impl Timeline {
+    /// seconds since the first test started
+    pub fn now(&self) -> f32 {
+        self.began.map_or(0., |b| b.elapsed().as_secs_f32())
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.busy.len()
+    }
+
+    /// when the last test ended, or now if some are still going
+    pub fn end(&self) -> f32 {
+        self.bars
+            .iter()
+            .map(|b| b.end.unwrap_or_else(|| self.now()))
+            .fold(0., f32::max)
+    }
+
+    /// `test` is the index into the tests
+    pub fn started(&mut self, test: usize, name: &str) {
+        let start = *self.began.get_or_insert_with(Instant::now);
+        let lane = match self.busy.iter().position(|b| !b) {
+            Some(l) => l,
+            None => {
+                self.busy.push(false);
+                self.busy.len() - 1
+            }
+        };
+        self.busy[lane] = true;
+        self.bars.push(Bar {
+            test,
+            name: name.to_string(),
+            start: start.elapsed().as_secs_f32(),
+            end: None,
+            lane,
+            outcome: Outcome::Running,
+        });
+    }
+
+    pub fn ended(&mut self, test: usize, outcome: Outcome) {
+        let now = self.now();
+        let Some(bar) = self
+            .bars
+            .iter_mut()
+            .rev()
+            .find(|b| b.end.is_none() && b.test == test)
+        else {
+            return;
+        };
+        bar.end = Some(now);
+        bar.outcome = outcome;
+        self.busy[bar.lane] = false;
+    }
+
+    /// seconds shown, of the run
+    pub fn span(&self) -> f32 {
+        self.end().max(0.001) / self.zoom.max(1.)
+    }
+
+    pub fn zoom(&mut self, by: f32) {
+        self.zoom = (self.zoom.max(1.) * by).clamp(1., 1024.);
+        self.follow();
+    }
+
+    /// moves the window so the selected bar is in it
+    fn follow(&mut self) {
+        let span = self.span();
+        if let Some(b) = self.selected.and_then(|i| self.bars.get(i)) {
+            if b.start < self.offset || b.start > self.offset + span * 0.9 {
+                self.offset = b.start - span * 0.1;
+            }
+        }
+        self.offset = self.offset.clamp(0., (self.end() - span).max(0.));
+    }
+
+    /// the next bar to start, or the one before
+    pub fn step(&mut self, forward: bool) {
+        let n = self.bars.len();
+        if n == 0 {
+            return;
+        }
+        self.selected = Some(match (self.selected, forward) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1).min(n - 1),
+            (Some(i), false) => i.saturating_sub(1),
+        });
+        self.follow();
+    }
+
+    /// the bar in the lane above or below that was running closest to the selected one
+    pub fn lane(&mut self, down: bool) {
+        let Some(b) = self.selected.and_then(|i| self.bars.get(i)) else {
+            return self.step(true);
+        };
+        let lane = match down {
+            true => b.lane + 1,
+            false => match b.lane.checked_sub(1) {
+                Some(l) => l,
+                None => return,
+            },
+        };
+        let mid = (b.start + b.end.unwrap_or_else(|| self.now())) / 2.;
+        let distance = |b: &Bar| {
+            let end = b.end.unwrap_or_else(|| self.now());
+            if (b.start..=end).contains(&mid) {
+                0.
+            } else {
+                (b.start - mid).abs().min((end - mid).abs())
+            }
+        };
+        if let Some(i) = (0..self.bars.len())
+            .filter(|&i| self.bars[i].lane == lane)
+            .min_by(|&a, &b| distance(&self.bars[a]).total_cmp(&distance(&self.bars[b])))
+        {
+            self.selected = Some(i);
+            self.follow();
+        }
+    }
+}
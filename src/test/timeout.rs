@@ -0,0 +1,88 @@
+//! how long a test may run before its killed
+#[derive(Default, Clone)]
+pub struct Timeouts {
+    /// seconds, for every test
+    pub all: Option<f32>,
+    /// seconds, for tests matching a pattern. the first match wins over `all`
+    pub by_name: Vec<(String, f32)>,
+}
+
+impl Timeouts {
+    pub fn of(&self, name: &str) -> Option<f32> {
+        self.by_name
+            .iter()
+            .find(|(p, _)| glob(p, name))
+            .map(|&(_, t)| t)
+            .or(self.all)
+    }
+}
+
+/// `PATTERN=SECS`, like `parser::*=5`
+pub fn parse(s: &str) -> Result<(String, f32), String> {
+    let (pattern, secs) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected PATTERN=SECS, got {s}"))?;
+    let secs = secs.parse().map_err(|e| format!("{secs}: {e}"))?;
+    Ok((pattern.to_string(), secs))
+}
+
+/// where `*` matches anything
+fn glob(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*`
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob("parser::expr::unary", "parser::expr::unary"));
+        assert!(!glob("parser::expr", "parser::expr::unary"));
+        assert!(glob("parser::*", "parser::expr::unary"));
+        assert!(glob("*::slow", "parser::stmt::slow"));
+        assert!(glob("parser::*::slow", "parser::stmt::slow"));
+        assert!(!glob("parser::*::slow", "lexer::stmt::slow"));
+        assert!(glob("*", ""));
+        // `**` is just a `*`
+        assert!(glob("parser::**", "parser::expr::unary"));
+        assert!(glob("**slow", "slow"));
+        // the pieces dont overlap
+        assert!(!glob("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(parse("parser::*=5"), Ok(("parser::*".to_string(), 5.)));
+        assert_eq!(parse("a=b=0.5"), Ok(("a=b".to_string(), 0.5)));
+        assert!(parse("parser::*").is_err());
+        assert!(parse("parser::*=soon").is_err());
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let t = Timeouts {
+            all: Some(60.),
+            by_name: vec![("*slow".into(), 5.), ("parser::*".into(), 10.)],
+        };
+        assert_eq!(t.of("parser::slow"), Some(5.));
+        assert_eq!(t.of("parser::expr"), Some(10.));
+        assert_eq!(t.of("lexer"), Some(60.));
+    }
+}
@@ -0,0 +1,119 @@
+//! tests as a module tree, from splitting their names on `::`
+use std::collections::{BTreeMap, HashMap};
+
+use crate::cargo::TestEvent;
+
+#[derive(Default, Clone, Copy)]
+pub struct Counts {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub running: usize,
+    /// summed, in seconds
+    pub time: f32,
+}
+
+impl Counts {
+    fn add(&mut self, t: &TestEvent) {
+        match t {
+            TestEvent::Started { .. } => self.running += 1,
+            TestEvent::Ok { exec_time, .. } => {
+                self.passed += 1;
+                self.time += exec_time;
+            }
+            TestEvent::Failed { exec_time, .. } => {
+                self.failed += 1;
+                self.time += exec_time;
+            }
+            TestEvent::Timeout { .. } => self.failed += 1,
+            TestEvent::Ignored { .. } => self.ignored += 1,
+        }
+    }
+}
+
+pub enum Kind {
+    Module {
+        counts: Counts,
+        open: bool,
+    },
+    /// index into the tests
+    Test(usize),
+}
+
+pub struct Row {
+    /// `parser::expr`, what gets collapsed
+    pub path: String,
+    /// `expr`
+    pub name: String,
+    pub depth: usize,
+    pub kind: Kind,
+}
+
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    test: Option<usize>,
+    counts: Counts,
+}
+
+/// the visible rows. `open` is what the user toggled,
+/// everything else is open if it has failures, or is all there is.
+pub fn rows(tests: &[TestEvent], visible: &[usize], open: &HashMap<String, bool>) -> Vec<Row> {
+    let mut root = Node::default();
+    for (i, t) in visible.iter().map(|&i| (i, &tests[i])) {
+        let mut node = &mut root;
+        for part in t.name().split("::") {
+            node.counts.add(t);
+            node = node.children.entry(part.to_string()).or_default();
+        }
+        node.counts.add(t);
+        node.test = Some(i);
+    }
+    let mut rows = vec![];
+    flatten(&root, "", 0, open, &mut rows);
+    rows
+}
+
+fn flatten(
+    node: &Node,
+    path: &str,
+    depth: usize,
+    open: &HashMap<String, bool>,
+    rows: &mut Vec<Row>,
+) {
+    let only = node.children.len() == 1;
+    for (name, child) in &node.children {
+        let path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{path}::{name}")
+        };
+        if let Some(i) = child.test {
+            rows.push(Row {
+                path: path.clone(),
+                name: name.clone(),
+                depth,
+                kind: Kind::Test(i),
+            });
+        }
+        if child.children.is_empty() {
+            continue;
+        }
+        let is_open = open
+            .get(&path)
+            .copied()
+            .unwrap_or(child.counts.failed != 0 || only);
+        rows.push(Row {
+            path: path.clone(),
+            name: name.clone(),
+            depth,
+            kind: Kind::Module {
+                counts: child.counts,
+                open: is_open,
+            },
+        });
+        if is_open {
+            flatten(child, &path, depth + 1, open, rows);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::test::panic::{shown, Shown};
+use crate::test::TestState;
+use crate::ui::*;
+
+pub fn backtrace<B: Backend>(f: &mut Frame<B>, state: &mut TestState, chunk: Rect) {
+    let Some(p) = state.panic() else { return };
+    let shown = shown(&p.backtrace, state.all_frames);
+    let mut l = Vec::with_capacity(shown.len());
+    for s in &shown {
+        match *s {
+            Shown::Hidden(n) => l.pl(Span::styled(
+                format!("  … {n} std frame{}", if n == 1 { "" } else { "s" }),
+                Style::default().dark_gray().italic(),
+            )),
+            Shown::Frame(i) => {
+                let frame = &p.backtrace[i];
+                let style = if frame.is_workspace() {
+                    Style::default().bold().cyan()
+                } else if frame.is_std() {
+                    Style::default().dark_gray()
+                } else {
+                    Style::default()
+                };
+                let mut line = vec![
+                    Span::styled(format!("{i:>3} "), Style::default().dark_gray()),
+                    Span::styled(frame.symbol.clone(), style),
+                ];
+                if let Some((file, ln, col)) = &frame.at {
+                    line.push(Span::styled(
+                        format!("  {file}:{ln}:{col}"),
+                        Style::default().blue().italic(),
+                    ));
+                }
+                l.pl(line);
+            }
+        }
+    }
+    state.frames.has(shown.len());
+    f.render_stateful_widget(
+        List::new(l)
+            .highlight_style(Style::default().on_light_green().italic())
+            .highlight_symbol("> ")
+            .block(
+                Block::default()
+                    .title(format!(
+                        "backtrace of {}",
+                        state.opened.map_or("", |i| state.tests[i].name())
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(Thick),
+            ),
+        chunk,
+        &mut state.frames.state,
+    );
+}
@@ -1,3 +1,4 @@
+use super::test_list::memory;
 use crate::ui::*;
 use crate::{
     cargo::TestEvent,
@@ -5,18 +6,11 @@ use crate::{
 };
 
 pub fn inspector<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
-    let Some(t) = state.test_list.selects(state) else {
+    let Some(i) = state.shown() else {
         return;
     };
     let b = Block::default().title("inspect test").borders(Borders::ALL);
-    let stdblock = || {
-        let b = Block::default().borders(Borders::ALL).title("stdout");
-        if state.screen == Screen::Stdout {
-            return b.border_type(Thick).title_style(Style::default().italic());
-        }
-        b
-    };
-    match t {
+    match &state.tests[i] {
         TestEvent::Ignored { name } => {
             f.render_widget(
                 Paragraph::new(ctext!("test {:bold_yellow} was ignored", name))
@@ -35,27 +29,31 @@ pub fn inspector<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
                 chunk,
             );
         }
-        TestEvent::Failed { name, stdout, .. } => {
-            if let Some(stdout) = stdout {
+        TestEvent::Failed { name, .. } => {
+            let stdout = state.output(i);
+            if let Some(stdout) = output_of(state, i, &stdout) {
+                let mut head = ctext!("test {:bold_red} failed", name);
+                head.lines.extend(exit(state, i));
+                if let Some(p) = state.panic() {
+                    head.lines.extend(super::panic::summary(&p));
+                }
                 let chunks = Layout::new()
                     .direction(Vertical)
-                    .constraints([Constraint::Percentage(10), Constraint::Percentage(90)])
+                    .constraints([Length(height(&head, chunk.width)), Min(1)])
                     .split(chunk);
                 f.render_widget(
-                    Paragraph::new(ctext!("test {:bold_red} failed", name))
+                    Paragraph::new(head)
                         .alignment(Alignment::Center)
-                        .block(b),
+                        .block(b)
+                        .wrap(Wrap { trim: true }),
                     chunks[0],
                 );
-                f.render_widget(
-                    Paragraph::new(<String as ansi_to_tui::IntoText>::into_text(stdout).unwrap())
-                        .block(stdblock())
-                        .scroll((state.stdout.scroll, 0)),
-                    chunks[1],
-                );
+                output(f, state, i, stdout, chunks[1]);
             } else {
+                let mut head = ctext!("test {:bold_red} failed", name);
+                head.lines.extend(exit(state, i));
                 f.render_widget(
-                    Paragraph::new(ctext!("test {:bold_red} failed", name))
+                    Paragraph::new(head)
                         .alignment(Alignment::Center)
                         .block(b)
                         .wrap(Wrap { trim: true }),
@@ -63,27 +61,30 @@ pub fn inspector<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
                 );
             }
         }
-        TestEvent::Ok { name, stdout, .. } => {
-            if let Some(stdout) = stdout {
+        TestEvent::Ok { name, .. } => {
+            let mut head = match state.attempts.get(&i) {
+                Some(a) => ctext!(
+                    "test {:bold_magenta} passed on attempt {}",
+                    name,
+                    a.len() + 1
+                ),
+                None => ctext!("test {:bold_green} passed", name),
+            };
+            head.lines.extend(exit(state, i));
+            let stdout = state.output(i);
+            if let Some(stdout) = output_of(state, i, &stdout) {
                 let chunks = Layout::new()
                     .direction(Vertical)
-                    .constraints([Constraint::Percentage(10), Constraint::Percentage(90)])
+                    .constraints([Length(height(&head, chunk.width)), Min(1)])
                     .split(chunk);
                 f.render_widget(
-                    Paragraph::new(ctext!("test {:bold_green} passed", name))
-                        .alignment(Alignment::Center)
-                        .block(b),
+                    Paragraph::new(head).alignment(Alignment::Center).block(b),
                     chunks[0],
                 );
-                f.render_widget(
-                    Paragraph::new(<String as ansi_to_tui::IntoText>::into_text(stdout).unwrap())
-                        .block(stdblock())
-                        .scroll((state.stdout.scroll, 0)),
-                    chunks[1],
-                );
+                output(f, state, i, stdout, chunks[1]);
             } else {
                 f.render_widget(
-                    Paragraph::new(ctext!("test {:bold_green} passed", name))
+                    Paragraph::new(head)
                         .alignment(Alignment::Center)
                         .block(b)
                         .wrap(Wrap { trim: true }),
@@ -102,3 +103,75 @@ pub fn inspector<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
         }
     }
 }
+
+/// how its process ended, if it ran alone
+fn exit<'a>(state: &TestState, i: usize) -> Option<Line<'a>> {
+    let e = state.exits.get(&i)?;
+    let mut l = vec![Span::styled(
+        e.why(),
+        match e.code {
+            Some(0) => Style::default().dark_gray(),
+            _ => Style::default().red(),
+        },
+    )];
+    if let Some(kb) = e.peak_rss {
+        l.push(Span::styled(
+            format!(", peak rss {}", memory(kb)),
+            Style::default().dark_gray(),
+        ));
+    }
+    Some(Line::from(l))
+}
+
+/// the stdout, or nothing if it only wrote to stderr
+fn output_of<'a>(state: &TestState, i: usize, stdout: &'a Option<String>) -> Option<&'a str> {
+    let stderr = state
+        .exits
+        .get(&i)
+        .is_some_and(|e| !e.stderr.trim().is_empty());
+    stdout.as_deref().or(stderr.then_some(""))
+}
+
+/// the stdout, and the stderr under it if it ran alone and wrote any
+fn output<B: Backend>(f: &mut Frame<B>, state: &TestState, i: usize, stdout: &str, chunk: Rect) {
+    let mut b = Block::default().borders(Borders::ALL).title("stdout");
+    if state.screen == Screen::Stdout {
+        b = b.border_type(Thick).title_style(Style::default().italic());
+    }
+    let stderr = state
+        .exits
+        .get(&i)
+        .map(|e| e.stderr.trim_end())
+        .filter(|e| !e.is_empty());
+    let chunks = Layout::new()
+        .direction(Vertical)
+        .constraints(match stderr {
+            Some(_) => [Percentage(60), Percentage(40)],
+            None => [Percentage(100), Percentage(0)],
+        })
+        .split(chunk);
+    f.render_widget(
+        Paragraph::new(<&str as ansi_to_tui::IntoText>::into_text(&stdout).unwrap())
+            .block(b)
+            .scroll((state.stdout.scroll, 0)),
+        chunks[0],
+    );
+    if let Some(stderr) = stderr {
+        f.render_widget(
+            Paragraph::new(<&str as ansi_to_tui::IntoText>::into_text(&stderr).unwrap())
+                .block(Block::default().borders(Borders::ALL).title("stderr")),
+            chunks[1],
+        );
+    }
+}
+
+/// of `t` wrapped in a bordered block `width` wide
+fn height(t: &Text, width: u16) -> u16 {
+    let inner = usize::from(width.saturating_sub(2)).max(1);
+    let rows = t
+        .lines
+        .iter()
+        .map(|l| l.width().div_ceil(inner).max(1))
+        .sum::<usize>();
+    u16::try_from(rows + 2).unwrap_or(u16::MAX)
+}
@@ -1,16 +1,29 @@
+mod backtrace;
 mod inspector;
+mod panic;
 mod progress;
+mod regressions;
+mod slow;
 pub mod stdout;
+mod stress;
 pub mod test_list;
-use super::Screen;
-use crate::cargo;
+mod timeline;
+mod tree;
+mod usage;
+use super::{Screen, View};
+use crate::cargo::{self, TestEvent};
 use crate::ui::*;
 
-pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut super::TestState, meta: &cargo::Metadata) {
+pub fn ui<B: Backend>(
+    f: &mut Frame<B>,
+    state: &mut super::TestState,
+    meta: &cargo::Metadata,
+    chunk: Rect,
+) {
     let chunks = Layout::default()
         .direction(Vertical)
         .constraints([Length(3), Min(1), Length(1)])
-        .split(f.size());
+        .split(chunk);
     let title_chunks = Layout::default()
         .direction(Horizontal)
         .constraints([Percentage(10), Percentage(80)])
@@ -29,49 +42,141 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, state: &mut super::TestState, meta: &car
         title_chunks[0],
     );
     progress::progress(f, state, title_chunks[1]);
-    if state.test_list.selects(state).is_some() {
+    state.settle();
+    if state.screen == Screen::Panic {
+        panic::panic(f, state, chunks[1]);
+    } else if state.screen == Screen::Backtrace {
+        backtrace::backtrace(f, state, chunks[1]);
+    } else if state.screen == Screen::Stress {
+        stress::stress(f, state, chunks[1]);
+    } else if state.screen == Screen::Timeline {
+        timeline::timeline(f, state, chunks[1]);
+    } else if state.screen == Screen::Usage {
+        usage::usage(f, state, chunks[1]);
+    } else if state.shown().is_some() {
         let main_panels = match state.screen {
             Screen::Main => Layout::default()
                 .direction(Horizontal)
                 .constraints([Percentage(80), Percentage(20)])
                 .split(chunks[1]),
-            Screen::Stdout => Layout::default()
+            _ => Layout::default()
                 .direction(Horizontal)
                 .constraints([Percentage(60), Percentage(40)])
                 .split(chunks[1]),
         };
-        test_list::test_list(f, state, main_panels[0]);
+        list(f, state, main_panels[0]);
         inspector::inspector(f, state, main_panels[1]);
     } else {
-        test_list::test_list(f, state, chunks[1]);
+        list(f, state, chunks[1]);
     }
     let footer_chunks = Layout::default()
         .direction(Horizontal)
-        .constraints([Percentage(50), Percentage(50)])
+        .constraints([Percentage(70), Percentage(30)])
         .split(chunks[2]);
     let usage = match state.screen {
-        Screen::Main => match state.test_list.selects(state) {
-            Some(t) if t.stdout().is_some() => {
+        Screen::Main => match state.selected().map(|i| (i, &state.tests[i])) {
+            Some((_, TestEvent::Failed { .. })) if state.panic().is_some_and(|p| !p.backtrace.is_empty()) => {
+                Paragraph::new(ctext!("press {blue}p{reset} for the panic | {blue}b{reset} for the backtrace | {green}right{reset} for the stdout | {blue}r R{reset} to rerun | {blue}S{reset} to stress"))
+            }
+            Some((_, TestEvent::Failed { .. })) if state.panic().is_some() => {
+                Paragraph::new(ctext!("press {blue}p{reset} for the panic | {green}right{reset} for the stdout | {blue}r{reset} to rerun it | {blue}R{reset} to rerun all failures | {blue}S{reset} to stress"))
+            }
+            Some((_, TestEvent::Failed { .. } | TestEvent::Timeout { .. })) if state.done => {
+                Paragraph::new(ctext!("press {green}right{reset} to view the stdout | {blue}r{reset} to rerun it | {blue}R{reset} to rerun all failures | {blue}S{reset} to stress"))
+            }
+            Some((i, _)) if state.output(i).is_some() => {
                 Paragraph::new(ctext!("press {green}right{reset} to view the stdout"))
             }
+            _ if state.view == View::Tree => Paragraph::new(ctext!(
+                "press {green}up{reset} or {red}down{reset} to change selection | press {blue}enter{reset} to open or close | press {blue}t{reset} for the list"
+            )),
             _ => Paragraph::new(ctext!(
-                "press {green}up{reset} or {red}down{reset} to change selection"
+                "press {blue}t{reset} for the module tree | {blue}/{reset} to search | {blue}f u i o{reset} for failed, running, ignored, slow | {blue}c{reset} to sort | {blue}S{reset} to stress | {blue}l m{reset} for the timeline, memory"
             )),
         },
         Screen::Stdout => {
             Paragraph::new(ctext!("press {blue}left{reset} to go back to tests | press {green}up{reset} or {red}down{reset} to scroll stdout"))
         }
+        Screen::Panic => {
+            Paragraph::new(ctext!("press {blue}left{reset} to go back to tests | press {green}up{reset} or {red}down{reset} to scroll | {blue}b{reset} for the backtrace"))
+        }
+        Screen::Stress => Paragraph::new(ctext!("press {blue}left{reset} to stop and go back to tests")),
+        Screen::Timeline => {
+            Paragraph::new(ctext!("press {green}arrows{reset} to pick a test | {blue}+ -{reset} to zoom | {blue}enter{reset} to jump to it | {blue}esc{reset} to go back"))
+        }
+        Screen::Usage => Paragraph::new(ctext!("press {blue}esc{reset} to go back to tests")),
+        Screen::Backtrace => {
+            Paragraph::new(ctext!("press {blue}left{reset} to go back | {blue}enter{reset} to open in your editor | {blue}h{reset} to toggle std frames"))
+        }
     };
     f.render_widget(usage, footer_chunks[0]);
     let status = match state.screen {
-        Screen::Main => match state.test_list.selects(state) {
-            Some(t) => Paragraph::new(ctext!("viewing test {:blue}", t.name())),
-            None => Paragraph::new("listing tests"),
+        Screen::Main => match state.selected() {
+            Some(i) => Paragraph::new(ctext!("viewing test {:blue}", state.tests[i].name())),
+            None => match state.module() {
+                Some((m, _)) => Paragraph::new(ctext!("viewing module {:blue}", m)),
+                None => Paragraph::new("listing tests"),
+            },
         },
         Screen::Stdout => Paragraph::new(ctext!(
             "viewing stdout of test {:blue}",
-            state.test_list.selects(state).unwrap().name()
+            state.opened.map_or("", |i| state.tests[i].name())
+        )),
+        Screen::Panic => Paragraph::new(ctext!(
+            "viewing the panic of test {:blue}",
+            state.opened.map_or("", |i| state.tests[i].name())
+        )),
+        Screen::Stress => Paragraph::new(ctext!(
+            "stressing test {:blue}",
+            state.stress.as_ref().map_or("", |s| &s.name)
+        )),
+        Screen::Backtrace => Paragraph::new(ctext!(
+            "viewing the backtrace of test {:blue}",
+            state.opened.map_or("", |i| state.tests[i].name())
         )),
+        Screen::Timeline => match state.timeline.selected {
+            Some(i) => {
+                let b = &state.timeline.bars[i];
+                let took = b.end.unwrap_or_else(|| state.timeline.now()) - b.start;
+                Paragraph::new(ctext!(
+                    "viewing {:blue}, {:.2}s in lane {}",
+                    b.name,
+                    took,
+                    b.lane + 1
+                ))
+            }
+            None => Paragraph::new("viewing the timeline"),
+        },
+        Screen::Usage => Paragraph::new("viewing memory and cpu"),
     };
     f.render_widget(status, footer_chunks[1]);
 }
+
+fn list<B: Backend>(f: &mut Frame<B>, state: &mut super::TestState, chunk: Rect) {
+    let slow = state.slow_running().len();
+    let chunk = if slow == 0 {
+        chunk
+    } else {
+        let chunks = Layout::default()
+            .direction(Vertical)
+            .constraints([Length(slow.min(slow::SHOWN) as u16 + 2), Min(1)])
+            .split(chunk);
+        slow::slow(f, state, chunks[0]);
+        chunks[1]
+    };
+    let n = state.regressions().len();
+    let chunk = if n == 0 {
+        chunk
+    } else {
+        let chunks = Layout::default()
+            .direction(Vertical)
+            .constraints([Min(1), Length(n.min(5) as u16 + 2)])
+            .split(chunk);
+        regressions::regressions(f, state, chunks[1]);
+        chunks[0]
+    };
+    match state.view {
+        View::List => test_list::test_list(f, state, chunk),
+        View::Tree => tree::tree(f, state, chunk),
+    }
+}
@@ -0,0 +1,102 @@
+use similar::{ChangeTag, TextDiff};
+
+use crate::test::panic::{pretty, Assert, Panic};
+use crate::test::TestState;
+use crate::ui::*;
+
+pub fn panic<B: Backend>(f: &mut Frame<B>, state: &mut TestState, chunk: Rect) {
+    let Some(p) = state.panic() else { return };
+    let mut l = vec![
+        Line::from(vec![
+            "panicked at ".into(),
+            Span::styled(p.location.clone(), Style::default().bold().blue()),
+            " in thread ".into(),
+            Span::styled(p.thread.clone(), Style::default().italic()),
+        ]),
+        Line::default(),
+    ];
+    match &p.assert {
+        Some(a) => {
+            // the message, without the values
+            let (first, _) = p
+                .message
+                .split_once("\n  left: ")
+                .unwrap_or((&p.message, ""));
+            l.push(Line::styled(
+                first.to_string(),
+                Style::default().bold().red(),
+            ));
+            l.push(Line::default());
+            l.extend(assert(a));
+        }
+        None => l.extend(
+            p.message
+                .lines()
+                .map(|m| Line::styled(m.to_string(), Style::default().red())),
+        ),
+    }
+    state.stdout.lines = u16::try_from(l.len()).unwrap_or(u16::MAX);
+    f.render_widget(
+        Paragraph::new(l)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "panic of {}",
+                        state.opened.map_or("", |i| state.tests[i].name())
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(Thick),
+            )
+            .scroll((state.stdout.scroll, 0)),
+        chunk,
+    );
+}
+
+/// the sides of a failed assert, diffed if they should have been equal
+fn assert<'a>(a: &Assert) -> Vec<Line<'a>> {
+    let (left, right) = (pretty(&a.left), pretty(&a.right));
+    if a.op == "!=" {
+        let mut l = vec![Line::styled("both sides are", Style::default().dark_gray())];
+        l.extend(left.lines().map(|s| Line::from(format!("  {s}"))));
+        return l;
+    }
+    let mut l = vec![
+        Line::styled("- left", Style::default().red()),
+        Line::styled("+ right", Style::default().green()),
+        Line::default(),
+    ];
+    let diff = TextDiff::from_lines(&left, &right);
+    for op in diff.ops() {
+        for change in diff.iter_inline_changes(op) {
+            let (sign, style) = match change.tag() {
+                ChangeTag::Delete => ("- ", Style::default().red()),
+                ChangeTag::Insert => ("+ ", Style::default().green()),
+                ChangeTag::Equal => ("  ", Style::default().dark_gray()),
+            };
+            let mut spans = vec![Span::styled(sign, style)];
+            for (emphasized, s) in change.iter_strings_lossy() {
+                let s = s.trim_end_matches('\n').to_string();
+                spans.push(match emphasized {
+                    true => Span::styled(s, style.bold().reversed()),
+                    false => Span::styled(s, style),
+                });
+            }
+            l.push(Line::from(spans));
+        }
+    }
+    l
+}
+
+/// the location and the first line of the message, for the inspector
+pub fn summary<'a>(p: &Panic) -> Vec<Line<'a>> {
+    vec![
+        Line::from(vec![
+            "at ".into(),
+            Span::styled(p.location.clone(), Style::default().blue()),
+        ]),
+        Line::styled(
+            p.message.lines().next().unwrap_or("").to_string(),
+            Style::default().red(),
+        ),
+    ]
+}
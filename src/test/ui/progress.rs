@@ -6,11 +6,13 @@ pub fn progress<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
         |n| (n as f32 / state.test_count as f32 * f32::from(chunk.width)).round() as usize * 3;
     const LINE: &str = "──────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────────";
     let mut passing = 0;
+    let mut flaky = 0;
     let mut ignored = 0;
     let mut failing = 0;
     let mut running = 0;
-    for test in &state.tests {
+    for (i, test) in state.tests.iter().enumerate() {
         match test {
+            _ if state.flaky_passed(i) => flaky += 1,
             TestEvent::Ok { .. } => passing += 1,
             TestEvent::Ignored { .. } => ignored += 1,
             TestEvent::Failed { .. } | TestEvent::Timeout { .. } => failing += 1,
@@ -18,9 +20,10 @@ pub fn progress<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
         }
     }
     let progress = Paragraph::new(ctext!(
-        "{:cyan}{:green}{:red}{:yellow}",
+        "{:cyan}{:green}{:magenta}{:red}{:yellow}",
         &LINE[..size(ignored)],
         &LINE[..size(passing)],
+        &LINE[..size(flaky)],
         &LINE[..size(failing)],
         &LINE[..size(running)],
     ));
@@ -0,0 +1,30 @@
+use super::test_list::time;
+use crate::test::TestState;
+use crate::ui::*;
+
+/// tests that got slower than they used to be
+pub fn regressions<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
+    let mut l = vec![];
+    for (i, median, now) in state.regressions() {
+        let mut line = vec![
+            Span::styled(state.tests[i].name().to_string(), Style::default().bold()),
+            "  ".into(),
+        ];
+        line.extend(time(median, Style::default().dark_gray()).spans);
+        line.push(" → ".into());
+        line.extend(time(now, Style::default().red()).spans);
+        line.push(Span::styled(
+            format!("  {:.1}x", now / median),
+            Style::default().red().italic(),
+        ));
+        l.pl(line);
+    }
+    f.render_widget(
+        List::new(l).block(
+            Block::default()
+                .title(Span::styled("regressions", Style::default().red()))
+                .borders(Borders::ALL),
+        ),
+        chunk,
+    );
+}
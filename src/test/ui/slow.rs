@@ -0,0 +1,33 @@
+use super::test_list::elapsed;
+use crate::test::TestState;
+use crate::ui::*;
+
+/// rows of the panel
+pub const SHOWN: usize = 3;
+
+/// the tests that have been running the longest, to catch a hang early
+pub fn slow<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
+    let slow = state.slow_running();
+    let mut l = vec![];
+    for &(i, _) in slow.iter().take(SHOWN) {
+        let t = &state.tests[i];
+        let mut line = vec![
+            Span::styled(t.name().to_string(), Style::default().bold()),
+            "  ".into(),
+        ];
+        line.extend(elapsed(state, i).spans);
+        l.pl(line);
+    }
+    let title = match slow.len() {
+        n if n > SHOWN => format!("currently slow ({} more)", n - SHOWN),
+        _ => "currently slow".to_string(),
+    };
+    f.render_widget(
+        List::new(l).block(
+            Block::default()
+                .title(Span::styled(title, Style::default().yellow()))
+                .borders(Borders::ALL),
+        ),
+        chunk,
+    );
+}
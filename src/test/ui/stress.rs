@@ -0,0 +1,97 @@
+use super::test_list::time;
+use crate::test::TestState;
+use crate::ui::*;
+use ratatui::widgets::{BarChart, Gauge};
+
+/// buckets of the duration histogram
+const BUCKETS: usize = 12;
+
+pub fn stress<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
+    let Some(s) = &state.stress else { return };
+    let chunks = Layout::default()
+        .direction(Vertical)
+        .constraints([Length(3), Length(3), Percentage(50), Min(3)])
+        .split(chunk);
+    let rate = s.pass_rate();
+    let color = match rate {
+        r if r >= 1. => Color::Green,
+        r if r >= 0.95 => Color::Yellow,
+        _ => Color::Red,
+    };
+    f.render_widget(
+        Gauge::default()
+            .ratio((s.done() as f64 / s.runs.max(1) as f64).min(1.))
+            .label(format!("{}/{} runs", s.done(), s.runs))
+            .gauge_style(Style::default().fg(color))
+            .block(
+                Block::default()
+                    .title(Line::from(vec![
+                        "stressing ".into(),
+                        Span::styled(s.name.clone(), Style::default().bold().blue()),
+                        if s.is_done() {
+                            Span::styled(" done", Style::default().green().italic())
+                        } else {
+                            Span::styled(" running", Style::default().yellow().italic())
+                        },
+                    ]))
+                    .borders(Borders::ALL),
+            ),
+        chunks[0],
+    );
+    let mut stats = vec![
+        "pass rate ".into(),
+        Span::styled(
+            format!("{:.1}%", rate * 100.),
+            Style::default().fg(color).bold(),
+        ),
+        Span::styled(
+            format!(" ({} failed)", s.done() - s.passed),
+            Style::default().dark_gray(),
+        ),
+    ];
+    for (name, q) in [("p50", 0.5), ("p90", 0.9), ("p99", 0.99)] {
+        if let Some(t) = s.percentile(q) {
+            stats.push(format!("  {name} ").into());
+            stats.extend(time(t, Style::default().cyan()).spans);
+        }
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(stats)).block(Block::default().borders(Borders::ALL)),
+        chunks[1],
+    );
+    let histogram = s
+        .histogram(BUCKETS)
+        .into_iter()
+        .map(|(from, n)| (format!("{:.1}ms", from * 1000.), n))
+        .collect::<Vec<_>>();
+    let data = histogram
+        .iter()
+        .map(|(l, n)| (l.as_str(), *n))
+        .collect::<Vec<_>>();
+    let inner = chunks[2].width.saturating_sub(2) as usize;
+    f.render_widget(
+        BarChart::default()
+            .data(&data)
+            .bar_width(((inner / BUCKETS).saturating_sub(1)).clamp(1, 12) as u16)
+            .bar_gap(1)
+            .bar_style(Style::default().cyan())
+            .value_style(Style::default().black().on_cyan())
+            .block(Block::default().title("durations").borders(Borders::ALL)),
+        chunks[2],
+    );
+    let mut l = vec![];
+    for (why, n) in &s.failures {
+        let mut lines = why.lines();
+        l.pl(vec![
+            Span::styled(format!("{n:>5}× "), Style::default().red().bold()),
+            Span::raw(lines.next().unwrap_or("").to_string()),
+        ]);
+        for line in lines {
+            l.pl(format!("       {line}"));
+        }
+    }
+    f.render_widget(
+        List::new(l).block(Block::default().title("failures").borders(Borders::ALL)),
+        chunks[3],
+    );
+}
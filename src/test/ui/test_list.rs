@@ -1,112 +1,251 @@
 use crate::cargo::TestEvent;
-use crate::test::TestState;
+use crate::test::columns::{duration, Column, Sort};
+use crate::test::history;
+use crate::test::{filter::Only, TestState};
+use crate::ui::ls::{decr, incr};
 use crate::ui::*;
+use ratatui::widgets::{Cell, Row, Table, TableState};
 use std::time::Duration;
 #[derive(Default)]
 pub struct TestList {
-    a: SList,
-    b: SList,
-    c: SList,
+    state: TableState,
+    itemc: usize,
 }
 
 impl TestList {
-    fn has(&mut self, n: usize) {
-        self.all().map(|a| a.has(n));
+    pub fn next(&mut self) {
+        let i = match self.itemc {
+            0 => None,
+            n => Some(self.state.selected().map_or(0, |x| incr(x, n))),
+        };
+        self.state.select(i);
     }
 
-    fn all(&mut self) -> [&mut SList; 3] {
-        [&mut self.a, &mut self.b, &mut self.c]
+    pub fn prev(&mut self) {
+        let i = match self.itemc {
+            0 => None,
+            n => Some(self.state.selected().map_or(0, |x| decr(x, n))),
+        };
+        self.state.select(i);
     }
 
-    pub fn next(&mut self) {
-        self.all().map(SList::next);
+    /// index into the tests
+    pub fn selects(&self, state: &TestState) -> Option<usize> {
+        state.visible.get(self.state.selected()?).copied()
     }
 
-    pub fn prev(&mut self) {
-        self.all().map(SList::prev);
+    pub fn select(&mut self, at: Option<usize>) {
+        self.state.select(at);
     }
+}
 
-    pub fn selects<'a>(&'a self, state: &'a TestState) -> Option<&TestEvent> {
-        state.tests.get(self.a.state.selected()?)
+pub fn time<'v>(secs: f32, style: Style) -> Line<'v> {
+    let dur = Duration::from_secs_f32(secs);
+    Line::styled(humantime::format_duration(dur).to_string(), style)
+}
+
+/// of a running test, to the tenth of a second so it ticks calmly
+pub fn elapsed<'v>(state: &TestState, i: usize) -> Line<'v> {
+    match state.elapsed(i) {
+        Some(secs) => Line::styled(
+            humantime::format_duration(Duration::from_millis((secs * 10.) as u64 * 100))
+                .to_string(),
+            state.elapsed_style(secs),
+        ),
+        None => Line::from(""),
     }
+}
 
-    pub fn stdout<'a>(&'a self, state: &'a TestState) -> Option<&str> {
-        self.selects(state)?.stdout()
+/// `12.5 MiB`, from kilobytes
+pub fn memory(kb: u64) -> String {
+    match kb {
+        kb if kb >= 1 << 20 => format!("{:.1} GiB", kb as f32 / (1 << 20) as f32),
+        kb if kb >= 1 << 10 => format!("{:.1} MiB", kb as f32 / (1 << 10) as f32),
+        kb => format!("{kb} KiB"),
     }
 }
 
-pub fn test_list<B: Backend>(f: &mut Frame<B>, state: &mut TestState, chunk: Rect) {
-    let mut tests = Vec::<ListItem>::new();
-    let mut test_side1 = Vec::<ListItem>::new();
-    let mut test_side2 = Vec::<ListItem>::new();
-    fn time<'v>(secs: f32) -> Line<'v> {
-        let dur = Duration::from_secs_f32(secs);
-        let time = humantime::format_duration(dur).to_string();
-        match (secs / 16.).round() as usize {
-            0 => Line::styled(time, Style::default().green()),
-            1 => Line::styled(time, Style::default().yellow()),
-            _ => Line::styled(time, Style::default().red()),
+/// `+35%`, against the median of earlier runs
+pub fn delta<'v>(state: &TestState, i: usize) -> Span<'v> {
+    match state.delta(i) {
+        Some((median, now)) => Span::styled(
+            format!("{:+.0}%", (now / median - 1.) * 100.),
+            match state.duration_style(i) {
+                s if s == Style::default() => s.dark_gray(),
+                s => s,
+            },
+        ),
+        None => Span::raw(""),
+    }
+}
+
+/// the name, with the chars the search matched underlined
+pub fn highlight<'a>(name: &str, matched: &[usize], style: Style) -> Line<'a> {
+    Line::from(
+        name.chars()
+            .enumerate()
+            .map(|(i, c)| match matched.contains(&i) {
+                true => Span::styled(c.to_string(), style.underlined().cyan()),
+                false => Span::styled(c.to_string(), style),
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn status<'a>(state: &TestState, i: usize) -> Span<'a> {
+    match &state.tests[i] {
+        _ if state.flaky_passed(i) => "flaky passed".magenta().italic(),
+        TestEvent::Started { .. } if state.warned.contains(&i) => {
+            "over 60s".yellow().bold().italic()
         }
+        TestEvent::Started { .. } => "in progress".yellow().italic(),
+        TestEvent::Ok { .. } => "passed".green().italic(),
+        TestEvent::Failed { .. } => "failed".red().bold().italic(),
+        TestEvent::Timeout { .. } => "timed out".red().bold().italic(),
+        TestEvent::Ignored { .. } => "ignored".yellow().italic(),
     }
-    for test in &state.tests {
-        match test {
-            TestEvent::Started { name } => {
-                tests.pl(name.bold().yellow());
-                test_side1.pl("in progress".yellow().italic());
-                test_side2.pl("");
-            }
-            TestEvent::Ok {
-                name, exec_time, ..
-            } => {
-                tests.pl(name.bold().green());
-                test_side1.pl("passed".green().italic());
-                test_side2.pl(time(*exec_time));
-            }
-            TestEvent::Failed {
-                name, exec_time, ..
-            } => {
-                tests.pl(name.bold().red());
-                test_side1.pl("failed".red().bold().italic());
-                test_side2.pl(time(*exec_time));
-            }
-            TestEvent::Timeout { name } => {
-                tests.pl(name.bold().red());
-                test_side1.pl("timed out".red().bold().italic());
-                test_side2.pl("");
+}
+
+/// the name gets whats left of `total`
+fn width(c: Column, columns: &[Column], total: u16) -> Constraint {
+    match c {
+        Column::Name => Length(columns.iter().filter(|&&c| c != Column::Name).fold(
+            total,
+            |w, &c| match width(c, columns, total) {
+                Length(n) => w.saturating_sub(n + 1),
+                _ => w,
+            },
+        )),
+        Column::Status => Length(12),
+        Column::Duration => Length(20),
+        Column::Delta => Length(8),
+        Column::Suite => Length(24),
+        // the strip, and flaky
+        Column::History => Length(history::RECENT as u16 + 7),
+        Column::Memory => Length(10),
+    }
+}
+
+pub fn test_list<B: Backend>(f: &mut Frame<B>, state: &mut TestState, chunk: Rect) {
+    let mut rows = vec![];
+    for &i in &state.visible {
+        let test = &state.tests[i];
+        let cells = state.columns.iter().map(|c| match c {
+            Column::Name => {
+                let style = match test {
+                    _ if state.flaky_passed(i) => Style::default().bold().magenta(),
+                    TestEvent::Ok { .. } => Style::default().bold().green(),
+                    TestEvent::Failed { .. } | TestEvent::Timeout { .. } => {
+                        Style::default().bold().red()
+                    }
+                    _ => Style::default().bold().yellow(),
+                };
+                Cell::from(highlight(
+                    test.name(),
+                    &state.filter.matched(test.name()),
+                    style,
+                ))
             }
-            TestEvent::Ignored { name } => {
-                tests.pl(name.bold().yellow());
-                test_side1.pl("ignored".yellow().italic());
-                test_side2.pl("");
+            Column::Status => Cell::from(status(state, i)),
+            Column::Delta => Cell::from(delta(state, i)),
+            Column::Duration => match test {
+                TestEvent::Ok { .. } | TestEvent::Failed { .. } => {
+                    Cell::from(time(duration(test), state.duration_style(i)))
+                }
+                TestEvent::Started { .. } => Cell::from(elapsed(state, i)),
+                _ => Cell::from(""),
+            },
+            Column::Suite => Cell::from(Span::styled(
+                state.suite_name(i).to_string(),
+                Style::default().dark_gray(),
+            )),
+            Column::History => {
+                let (recent, flaky) = state.history(i);
+                let mut l = recent
+                    .into_iter()
+                    .map(|passed| match passed {
+                        true => Span::styled("▪", Style::default().green()),
+                        false => Span::styled("▪", Style::default().red()),
+                    })
+                    .collect::<Vec<_>>();
+                if flaky {
+                    l.push(Span::styled(" flaky", Style::default().magenta().italic()));
+                }
+                Cell::from(Line::from(l))
             }
-        }
+            Column::Memory => Cell::from(
+                state
+                    .exits
+                    .get(&i)
+                    .and_then(|e| e.peak_rss)
+                    .map(memory)
+                    .unwrap_or_default(),
+            ),
+        });
+        rows.push(Row::new(cells));
     }
-    let sides = Layout::default()
-        .direction(Horizontal)
-        .constraints([Percentage(80), Percentage(10), Percentage(10)])
-        .split(chunk);
-    let hl = Style::default().on_light_green().italic();
-    state.test_list.has(tests.len());
+    let header = Row::new(state.columns.iter().map(|&c| {
+        let arrow = match state.sort {
+            Sort {
+                by: Some(by),
+                reverse,
+            } if by == c => {
+                if reverse {
+                    " ▼"
+                } else {
+                    " ▲"
+                }
+            }
+            _ => "",
+        };
+        Cell::from(format!("{}{arrow}", c.name()))
+    }))
+    .style(Style::default().bold().dark_gray());
+    // the borders and the highlight symbol
+    let total = chunk.width.saturating_sub(4);
+    let widths = state
+        .columns
+        .iter()
+        .map(|&c| width(c, &state.columns, total))
+        .collect::<Vec<_>>();
+    state.test_list.itemc = rows.len();
     f.render_stateful_widget(
-        List::new(tests)
-            .highlight_style(hl)
+        Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .highlight_style(Style::default().on_light_green().italic())
             .highlight_symbol("> ")
-            .block(Block::default().borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM)),
-        sides[0],
-        &mut state.test_list.a.state,
-    );
-    f.render_stateful_widget(
-        List::new(test_side1)
-            .highlight_style(hl)
-            .block(Block::default().borders(Borders::TOP | Borders::BOTTOM)),
-        sides[1],
-        &mut state.test_list.b.state,
-    );
-    f.render_stateful_widget(
-        List::new(test_side2)
-            .highlight_style(hl)
-            .block(Block::default().borders(Borders::TOP | Borders::BOTTOM | Borders::RIGHT)),
-        sides[2],
-        &mut state.test_list.c.state,
+            .block(Block::default().title(title(state)).borders(Borders::ALL)),
+        chunk,
+        &mut state.test_list.state,
     );
 }
+
+/// the search and filter, if any
+pub fn title<'a>(state: &TestState) -> Line<'a> {
+    let f = &state.filter;
+    let mut t = vec![];
+    if f.typing || !f.query.is_empty() {
+        t.push(Span::styled(
+            format!("/{}", f.query),
+            Style::default().cyan(),
+        ));
+        if f.typing {
+            t.push("▏".into());
+        }
+        t.push(" ".into());
+    }
+    if f.only != Only::All {
+        t.push(Span::styled(
+            format!("only {} ", f.only.name()),
+            Style::default().yellow(),
+        ));
+    }
+    if f.is_active() {
+        t.push(Span::styled(
+            format!("({} of {})", state.visible.len(), state.tests.len()),
+            Style::default().dark_gray(),
+        ));
+    }
+    Line::from(t)
+}
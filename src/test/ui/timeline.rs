@@ -0,0 +1,89 @@
+use crate::test::timeline::Outcome;
+use crate::test::TestState;
+use crate::ui::*;
+
+/// labels on the time axis
+const TICKS: usize = 5;
+
+/// one row per slot, each test a bar from its start to its end
+pub fn timeline<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
+    let t = &state.timeline;
+    let block = Block::default()
+        .title(Line::from(vec![
+            "timeline ".into(),
+            Span::styled(format!("{} lanes", t.lanes()), Style::default().bold()),
+            Span::styled(
+                format!("  {:.2}s shown, zoomed {}x", t.span(), t.zoom.max(1.)),
+                Style::default().dark_gray(),
+            ),
+        ]))
+        .borders(Borders::ALL)
+        .border_type(Thick);
+    let inner = block.inner(chunk);
+    f.render_widget(block, chunk);
+    let width = usize::from(inner.width);
+    if width == 0 || inner.height < 2 {
+        return;
+    }
+    // the last row is the axis
+    let rows = usize::from(inner.height - 1).min(t.lanes());
+    let span = t.span();
+    let per_cell = span / width as f32;
+    let now = t.now();
+    let mut lanes = vec![vec![(' ', Style::default()); width]; rows];
+    for (i, b) in t.bars.iter().enumerate() {
+        let end = b.end.unwrap_or(now);
+        if b.lane >= rows || end < t.offset || b.start > t.offset + span {
+            continue;
+        }
+        let from = ((b.start - t.offset) / per_cell).max(0.) as usize;
+        if from >= width {
+            continue;
+        }
+        let to = (((end - t.offset) / per_cell).ceil() as usize).clamp(from + 1, width);
+        let color = match b.outcome {
+            Outcome::Running => Color::Yellow,
+            Outcome::Passed if state.attempts.contains_key(&b.test) => Color::Magenta,
+            Outcome::Passed => Color::Green,
+            Outcome::Failed => Color::Red,
+            Outcome::Ignored => Color::Cyan,
+            Outcome::Killed => Color::DarkGray,
+        };
+        let style = match t.selected == Some(i) {
+            true => Style::default()
+                .bg(Color::LightGreen)
+                .fg(Color::Black)
+                .bold(),
+            false => Style::default().bg(color).fg(Color::Black),
+        };
+        let mut name = std::iter::once('▏').chain(b.name.chars());
+        for cell in &mut lanes[b.lane][from..to] {
+            *cell = (name.next().unwrap_or(' '), style);
+        }
+    }
+    let mut l = lanes
+        .into_iter()
+        .map(|lane| {
+            Line::from(
+                lane.into_iter()
+                    .map(|(c, s)| Span::styled(c.to_string(), s))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    l.resize(usize::from(inner.height - 1), Line::default());
+    let mut axis = vec![' '; width];
+    for tick in 0..TICKS {
+        let at = tick * (width - 1) / (TICKS - 1);
+        let label = format!("{:.2}s", t.offset + at as f32 * per_cell);
+        let at = at.min(width.saturating_sub(label.len()));
+        for (cell, c) in axis[at..].iter_mut().zip(label.chars()) {
+            *cell = c;
+        }
+    }
+    l.push(Line::styled(
+        axis.into_iter().collect::<String>(),
+        Style::default().dark_gray(),
+    ));
+    f.render_widget(Paragraph::new(l), inner);
+}
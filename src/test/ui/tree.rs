@@ -0,0 +1,83 @@
+use super::test_list::{delta, elapsed, highlight, time, title};
+use crate::cargo::TestEvent;
+use crate::test::{tree, TestState};
+use crate::ui::*;
+
+pub fn tree<B: Backend>(f: &mut Frame<B>, state: &mut TestState, chunk: Rect) {
+    let rows = state.rows();
+    let mut l = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let mut line = vec![Span::raw("    ".repeat(row.depth))];
+        match row.kind {
+            tree::Kind::Module { counts, open } => {
+                line.push(Span::styled(
+                    if open { "▾ " } else { "▸ " },
+                    Style::default().dark_gray(),
+                ));
+                let style = match counts {
+                    c if c.failed != 0 => Style::default().bold().red(),
+                    c if c.running != 0 => Style::default().bold().yellow(),
+                    _ => Style::default().bold().blue(),
+                };
+                line.push(Span::styled(row.name.clone(), style));
+                for (n, what, style) in [
+                    (counts.passed, "passed", Style::default().green()),
+                    (counts.failed, "failed", Style::default().red()),
+                    (counts.ignored, "ignored", Style::default().cyan()),
+                    (counts.running, "running", Style::default().yellow()),
+                ] {
+                    if n != 0 {
+                        line.push(Span::styled(format!(" {n} {what}"), style.italic()));
+                    }
+                }
+                line.push(" ".into());
+                line.extend(time(counts.time, Style::default().dark_gray()).spans);
+            }
+            tree::Kind::Test(i) => {
+                line.push("  ".into());
+                let t = &state.tests[i];
+                let (style, status) = match t {
+                    _ if state.flaky_passed(i) => (Style::default().magenta(), "flaky passed"),
+                    TestEvent::Started { .. } if state.warned.contains(&i) => {
+                        (Style::default().yellow(), "over 60s")
+                    }
+                    TestEvent::Started { .. } => (Style::default().yellow(), "in progress"),
+                    TestEvent::Ok { .. } => (Style::default().green(), "passed"),
+                    TestEvent::Failed { .. } => (Style::default().red(), "failed"),
+                    TestEvent::Timeout { .. } => (Style::default().red(), "timed out"),
+                    TestEvent::Ignored { .. } => (Style::default().yellow(), "ignored"),
+                };
+                // the search matched the whole name, and this is the end of it
+                let skip = t.name().chars().count() - row.name.chars().count();
+                let m = state
+                    .filter
+                    .matched(t.name())
+                    .into_iter()
+                    .filter_map(|i| i.checked_sub(skip))
+                    .collect::<Vec<_>>();
+                line.extend(highlight(&row.name, &m, style.bold()).spans);
+                line.push(Span::styled(format!(" {status}"), style.italic()));
+                if let TestEvent::Ok { exec_time, .. } | TestEvent::Failed { exec_time, .. } = t {
+                    line.push(" ".into());
+                    line.extend(time(*exec_time, state.duration_style(i)).spans);
+                    line.push(" ".into());
+                    line.push(delta(state, i));
+                }
+                if let TestEvent::Started { .. } = t {
+                    line.push(" ".into());
+                    line.extend(elapsed(state, i).spans);
+                }
+            }
+        }
+        l.pl(line);
+    }
+    state.tree.has(rows.len());
+    f.render_stateful_widget(
+        List::new(l)
+            .highlight_style(Style::default().on_light_green().italic())
+            .highlight_symbol("> ")
+            .block(Block::default().title(title(state)).borders(Borders::ALL)),
+        chunk,
+        &mut state.tree.state,
+    );
+}
@@ -0,0 +1,143 @@
+use super::test_list::memory;
+use crate::test::{usage::Binary, TestState};
+use crate::ui::*;
+use ratatui::{
+    symbols::Marker,
+    widgets::{Axis, Chart, Dataset, GraphType},
+};
+
+const COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Green,
+    Color::Blue,
+    Color::Red,
+];
+
+/// memory and cpu of each test binary over time, and their peaks
+pub fn usage<B: Backend>(f: &mut Frame<B>, state: &TestState, chunk: Rect) {
+    let binaries = &state.usage.binaries;
+    if binaries.is_empty() {
+        f.render_widget(
+            Paragraph::new("no test binaries have run yet")
+                .alignment(Alignment::Center)
+                .block(Block::default().title("usage").borders(Borders::ALL)),
+            chunk,
+        );
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Vertical)
+        .constraints([Percentage(55), Min(1), Length(binaries.len() as u16 + 2)])
+        .split(chunk);
+    let end = state.usage.end().max(1.);
+    // MiB
+    let rss = binaries
+        .iter()
+        .map(|b| {
+            b.samples
+                .iter()
+                .map(|s| (s.at, s.rss as f64 / 1024.))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let top = rss.iter().flatten().map(|&(_, m)| m).fold(1., f64::max);
+    chart(
+        f,
+        binaries,
+        &rss,
+        "memory",
+        end,
+        [memory(0), memory((top * 1024.) as u64)],
+        top,
+        chunks[0],
+    );
+    let cpu = binaries
+        .iter()
+        .map(|b| b.samples.iter().map(|s| (s.at, s.cpu)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let cores = cpu
+        .iter()
+        .flatten()
+        .map(|&(_, c)| c)
+        .fold(1., f64::max)
+        .ceil();
+    chart(
+        f,
+        binaries,
+        &cpu,
+        "cpu",
+        end,
+        ["0".into(), format!("{cores} cores")],
+        cores,
+        chunks[1],
+    );
+    let mut l = vec![];
+    for (b, c) in binaries.iter().zip(COLORS.iter().cycle()) {
+        let biggest = state.biggest(&b.suite);
+        // short tests can come and go between samples, but not past their exit
+        let peak = b.peak.max(biggest.map_or(0, |(_, kb)| kb));
+        let mut line = vec![
+            Span::styled(format!("█ {} ", b.suite), Style::default().fg(*c)),
+            Span::styled(format!("peak {} ", memory(peak)), Style::default().yellow()),
+            Span::styled(
+                format!("cpu {:.2}s", b.cpu_time),
+                Style::default().dark_gray(),
+            ),
+        ];
+        if let Some((name, kb)) = biggest {
+            line.push(Span::styled(
+                format!(" | biggest test {name} at {}", memory(kb)),
+                Style::default().dark_gray(),
+            ));
+        }
+        l.pl(line);
+    }
+    f.render_widget(
+        List::new(l).block(Block::default().title("per suite").borders(Borders::ALL)),
+        chunks[2],
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn chart<B: Backend>(
+    f: &mut Frame<B>,
+    binaries: &[Binary],
+    data: &[Vec<(f64, f64)>],
+    title: &str,
+    end: f64,
+    labels: [String; 2],
+    top: f64,
+    chunk: Rect,
+) {
+    let datasets = binaries
+        .iter()
+        .zip(data)
+        .zip(COLORS.iter().cycle())
+        .map(|((b, d), c)| {
+            Dataset::default()
+                .name(b.suite.clone())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*c))
+                .data(d)
+        })
+        .collect::<Vec<_>>();
+    let [low, high] = labels;
+    f.render_widget(
+        Chart::new(datasets)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .x_axis(
+                Axis::default()
+                    .bounds([0., end])
+                    .labels(vec!["0s".into(), Span::raw(format!("{end:.0}s"))]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0., top])
+                    .labels(vec![Span::raw(low), Span::raw(high)]),
+            ),
+        chunk,
+    );
+}
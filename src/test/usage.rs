@@ -0,0 +1,119 @@
+//! memory and cpu of the running test binaries, sampled from `/proc`
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{proc, runner::TestBinary};
+
+/// between samples
+const EVERY: Duration = Duration::from_millis(250);
+
+pub struct Sample {
+    /// seconds since the first sample
+    pub at: f64,
+    /// kilobytes, of all its processes
+    pub rss: u64,
+    /// cores busy
+    pub cpu: f64,
+}
+
+pub struct Binary {
+    /// like `src/main.rs`
+    pub suite: String,
+    pub samples: Vec<Sample>,
+    /// kilobytes, of its biggest process
+    pub peak: u64,
+    /// seconds
+    pub cpu_time: f64,
+}
+
+#[derive(Default)]
+pub struct Usage {
+    began: Option<Instant>,
+    last: Option<Instant>,
+    pub binaries: Vec<Binary>,
+    /// of each process, at the last sample
+    ticks: HashMap<u32, u64>,
+}
+
+impl Usage {
+    /// the processes below us that are test binaries, whoever started them
+    pub fn sample(&mut self, binaries: &[TestBinary], cwd: &Path) {
+        if binaries.is_empty() || self.last.is_some_and(|l| l.elapsed() < EVERY) {
+            return;
+        }
+        let now = Instant::now();
+        let dt = self.last.map_or(EVERY, |l| now - l).as_secs_f64();
+        self.last = Some(now);
+        let at = self.began.get_or_insert(now).elapsed().as_secs_f64();
+        let mut ticks = HashMap::new();
+        // (rss, ticks, peak) of each binary
+        let mut of = HashMap::<usize, (u64, u64, u64)>::new();
+        for pid in proc::descendants(std::process::id()) {
+            let Some(exe) = proc::exe(pid) else { continue };
+            let Some(b) = binaries.iter().find(|b| b.path.as_std_path() == exe) else {
+                continue;
+            };
+            let Some(stat) = proc::stat(pid) else {
+                continue;
+            };
+            let i = self.binary(b.suite(cwd));
+            let e = of.entry(i).or_default();
+            e.0 += proc::rss(pid).unwrap_or(0);
+            // new processes did all theirs since the last sample
+            e.1 += stat
+                .ticks
+                .saturating_sub(self.ticks.get(&pid).copied().unwrap_or(0));
+            e.2 = e.2.max(proc::peak_rss(pid).unwrap_or(0));
+            ticks.insert(pid, stat.ticks);
+        }
+        self.ticks = ticks;
+        for (i, b) in self.binaries.iter_mut().enumerate() {
+            let Some(&(rss, ticks, peak)) = of.get(&i) else {
+                // it exited, so drop the line to zero once
+                if b.samples.last().is_some_and(|s| s.rss != 0) {
+                    b.samples.push(Sample {
+                        at,
+                        rss: 0,
+                        cpu: 0.,
+                    });
+                }
+                continue;
+            };
+            let cpu = ticks as f64 / proc::ticks();
+            b.cpu_time += cpu;
+            b.peak = b.peak.max(peak);
+            b.samples.push(Sample {
+                at,
+                rss,
+                cpu: cpu / dt,
+            });
+        }
+    }
+
+    fn binary(&mut self, suite: String) -> usize {
+        match self.binaries.iter().position(|b| b.suite == suite) {
+            Some(i) => i,
+            None => {
+                self.binaries.push(Binary {
+                    suite,
+                    samples: vec![],
+                    peak: 0,
+                    cpu_time: 0.,
+                });
+                self.binaries.len() - 1
+            }
+        }
+    }
+
+    /// seconds since the first sample
+    pub fn end(&self) -> f64 {
+        self.binaries
+            .iter()
+            .filter_map(|b| b.samples.last())
+            .map(|s| s.at)
+            .fold(0., f64::max)
+    }
+}
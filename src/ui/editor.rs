@@ -0,0 +1,44 @@
+//! opening files in the users editor
+use anyhow::Result;
+use crossterm::{
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::prelude::*;
+use std::path::Path;
+use std::process::Command;
+
+/// `$VISUAL` or `$EDITOR`, with the terminal handed over until it exits
+pub fn open<B: Backend>(
+    terminal: &mut Terminal<B>,
+    file: &Path,
+    line: u32,
+    col: u32,
+) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let mut proc = Command::new(program);
+    proc.args(words);
+    let name = Path::new(program).file_stem().and_then(|s| s.to_str());
+    let file = file.display();
+    match name {
+        Some("code" | "codium") => proc.arg("-g").arg(format!("{file}:{line}:{col}")),
+        Some("hx" | "helix" | "subl" | "zed") => proc.arg(format!("{file}:{line}:{col}")),
+        // vi, emacs, nano, and most others
+        _ => proc.arg(format!("+{line}")).arg(file.to_string()),
+    };
+    log::trace!("running {proc:?}");
+    disable_raw_mode()?;
+    execute!(std::io::stdout(), LeaveAlternateScreen)?;
+    let status = proc.status();
+    enable_raw_mode()?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    if !status?.success() {
+        log::warn!("{editor} failed");
+    }
+    Ok(())
+}
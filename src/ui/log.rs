@@ -0,0 +1,55 @@
+use super::*;
+use crate::logger;
+use crossterm::event::KeyCode;
+
+#[derive(Default)]
+pub struct Log {
+    /// lines from the bottom, 0 follows the log
+    pub scroll: usize,
+}
+
+impl Log {
+    pub fn key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up | KeyCode::Char('w') => self.scroll += 1,
+            KeyCode::Down | KeyCode::Char('s') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageUp => self.scroll += 20,
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(20),
+            KeyCode::End => self.scroll = 0,
+            _ => {}
+        }
+    }
+}
+
+pub fn log<B: Backend>(f: &mut Frame<B>, state: &mut Log, chunk: Rect) {
+    let chunks = Layout::default()
+        .direction(Vertical)
+        .constraints([Min(1), Length(1)])
+        .split(chunk);
+    let height = chunks[0].height.saturating_sub(2) as usize;
+    let mut t = logger::lines(|lines| {
+        state.scroll = state.scroll.min(lines.len().saturating_sub(1));
+        let end = lines.len() - state.scroll.min(lines.len());
+        let mut t = Text::default();
+        for line in lines.range(end.saturating_sub(height)..end) {
+            t.extend(ansi_to_tui::IntoText::into_text(line).unwrap_or_default());
+        }
+        t
+    });
+    // multi line records
+    t.lines.drain(..t.lines.len().saturating_sub(height));
+    let title = match state.scroll {
+        0 => "log".to_string(),
+        n => format!("log ({n} up)"),
+    };
+    f.render_widget(
+        Paragraph::new(t).block(Block::default().title(title).borders(Borders::ALL)),
+        chunks[0],
+    );
+    f.render_widget(
+        Paragraph::new(ctext!(
+            "press {green}up{reset} or {red}down{reset} to scroll | press {blue}end{reset} to follow"
+        )),
+        chunks[1],
+    );
+}
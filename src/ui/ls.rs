@@ -7,7 +7,7 @@ pub struct SList {
 }
 
 pub const fn incr(what: usize, cap: usize) -> usize {
-    if what > cap - 1 {
+    if what >= cap - 1 {
         0
     } else {
         what + 1
@@ -24,11 +24,17 @@ pub const fn decr(what: usize, cap: usize) -> usize {
 
 impl SList {
     pub fn next(&mut self) {
+        if self.itemc == 0 {
+            return self.state.select(None);
+        }
         let i = self.state.selected().map_or(0, |x| incr(x, self.itemc));
         self.state.select(Some(i));
     }
 
     pub fn prev(&mut self) {
+        if self.itemc == 0 {
+            return self.state.select(None);
+        }
         let i = self.state.selected().map_or(0, |x| decr(x, self.itemc));
         self.state.select(Some(i));
     }
@@ -0,0 +1,108 @@
+//! just enough markdown for `--explain`: headings, inline code, and highlighted rust blocks
+use ratatui::prelude::*;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+pub fn markdown(s: &str) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    // Some(is rust) when in a code block
+    let mut code = None;
+    for line in s.lines() {
+        if let Some(tag) = line.trim_start().strip_prefix("```") {
+            code = match code {
+                Some(_) => None,
+                // ```compile_fail,E0502 and friends are rust too
+                None => Some(
+                    tag.is_empty()
+                        || tag.split(',').any(|t| {
+                            t.starts_with("rust")
+                                || t.starts_with("compile_fail")
+                                || t.starts_with("edition")
+                                || matches!(t, "ignore" | "no_run" | "should_panic")
+                        }),
+                ),
+            };
+            continue;
+        }
+        // hidden lines, as rustdoc does
+        if code == Some(true) && (line == "#" || line.starts_with("# ")) {
+            continue;
+        }
+        lines.push(match code {
+            Some(true) => rust(line),
+            Some(false) => Line::styled(line.to_string(), Style::default().dark_gray()),
+            None => text(line),
+        });
+    }
+    lines
+}
+
+fn text(line: &str) -> Line<'static> {
+    if let Some(h) = line.strip_prefix('#') {
+        let h = h.trim_start_matches('#').trim();
+        return Line::styled(h.to_string(), Style::default().bold().underlined().cyan());
+    }
+    // `code` is every other piece
+    Line::from(
+        line.split('`')
+            .enumerate()
+            .map(|(i, s)| match i % 2 {
+                0 => Span::raw(s.to_string()),
+                _ => Span::styled(s.to_string(), Style::default().yellow()),
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn rust(line: &str) -> Line<'static> {
+    let mut spans = vec![];
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let (len, style) = if rest.starts_with("//") {
+            (rest.len(), Style::default().dark_gray().italic())
+        } else if c == '"' {
+            let end = rest[1..].find('"').map_or(rest.len(), |e| e + 2);
+            (end, Style::default().green())
+        } else if c == '\'' && rest[1..].starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            // 'a, or 'a'
+            let word = 1 + rest[1..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len() - 1);
+            if rest[word..].starts_with('\'') {
+                (word + 1, Style::default().green())
+            } else {
+                (word, Style::default().cyan())
+            }
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.')
+                .unwrap_or(rest.len());
+            (end, Style::default().yellow())
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let style = if KEYWORDS.contains(&word) {
+                Style::default().magenta()
+            } else if rest[end..].starts_with('!') {
+                Style::default().cyan()
+            } else if word.starts_with(char::is_uppercase) {
+                Style::default().blue()
+            } else {
+                Style::default()
+            };
+            (end + usize::from(rest[end..].starts_with('!')), style)
+        } else {
+            (c.len_utf8(), Style::default())
+        };
+        spans.push(Span::styled(rest[..len].to_string(), style));
+        rest = &rest[len..];
+    }
+    Line::from(spans)
+}
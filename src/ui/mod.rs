@@ -1,4 +1,7 @@
+pub mod editor;
+pub mod log;
 pub mod ls;
+pub mod markdown;
 pub(crate) use crate::ctext;
 pub use ls::SList;
 pub use ratatui::{